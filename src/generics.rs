@@ -5,9 +5,13 @@
     Most commonly, two things in Rust are generic: structs and functions.
 */
 
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::fmt::Debug;
 use std::fmt::Formatter;
+use std::hash::Hash;
+use std::ops::Add;
 
 /*
     Generic Structs
@@ -73,6 +77,558 @@ impl<T> SortedVector<T> {
     /* What should we implement for SortedVector? */
 }
 
+// Methods can add their own bounds with a where clause,
+// so only SortedVectors of numbers get moving_average.
+impl<T> SortedVector<T> {
+    // Average of each consecutive window of `window` elements.
+    // Empty if window is 0 or longer than the vector.
+    pub fn moving_average(&self, window: usize) -> Vec<f64>
+    where
+        T: Into<f64> + Copy,
+    {
+        if window == 0 {
+            return Vec::new();
+        }
+        self.sv
+            .windows(window)
+            .map(|w| w.iter().map(|&x| x.into()).sum::<f64>() / window as f64)
+            .collect()
+    }
+
+    // p-th percentile (p from 0.0 to 100.0), interpolating linearly
+    // between the two closest ranks. None if p is out of range or
+    // there is no data.
+    pub fn percentile(&self, p: f64) -> Option<f64>
+    where
+        T: Into<f64> + Copy + Ord,
+    {
+        if self.sv.is_empty() || !(0.0..=100.0).contains(&p) {
+            return None;
+        }
+        // Copy is a subtrait of Clone, so sorted_data (below) applies
+        let data = self.sorted_data();
+        let rank = p / 100.0 * (data.len() - 1) as f64;
+        let lower: f64 = data[rank.floor() as usize].into();
+        let upper: f64 = data[rank.ceil() as usize].into();
+        Some(lower + (upper - lower) * rank.fract())
+    }
+
+    // Removing elements never reorders the rest,
+    // so a sorted vector stays sorted.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, pred: F) {
+        self.sv.retain(pred);
+        self.length = self.sv.len();
+    }
+
+    // Keep only the first of each run of elements with the same key.
+    // Like retain, this never reorders anything.
+    pub fn dedup_by_key<K: PartialEq, F: FnMut(&T) -> K>(&mut self, key: F) {
+        dedup_by_key_sorted(&mut self.sv, key);
+        self.length = self.sv.len();
+    }
+}
+
+#[test]
+fn test_moving_average() {
+    let v = SortedVector::new(vec![1, 2, 3, 4]);
+    assert_eq!(v.moving_average(2), vec![1.5, 2.5, 3.5]);
+    assert!(v.moving_average(0).is_empty());
+    assert!(v.moving_average(5).is_empty());
+}
+
+#[test]
+fn test_percentile() {
+    let v = SortedVector::new(vec![4, 1, 3, 2]);
+    assert_eq!(v.percentile(50.0), Some(2.5));
+    assert_eq!(v.percentile(0.0), Some(1.0));
+    assert_eq!(v.percentile(100.0), Some(4.0));
+    assert_eq!(v.percentile(101.0), None);
+    assert_eq!(SortedVector::<u8>::new(vec![]).percentile(50.0), None);
+}
+
+#[test]
+fn test_retain() {
+    let mut v = SortedVector::new(vec![1, 2, 3, 4, 5, 6]);
+    v.retain(|x| x % 2 == 0);
+    assert_eq!(v.sv, vec![2, 4, 6]);
+    assert_eq!(v.length, 3);
+}
+
+// Back to the .sort() problem from new(): if the whole impl block
+// requires T: Ord, then inside it we are allowed to sort.
+impl<T: Ord> SortedVector<T> {
+    // Like new(), but actually sorts the data
+    pub fn from_unsorted(mut raw_data: Vec<T>) -> SortedVector<T> {
+        raw_data.sort();
+        let length = raw_data.len();
+        SortedVector { sv: raw_data, is_sorted: true, length }
+    }
+
+    // sv is public, so anyone can push to it directly and leave
+    // is_sorted (and length) out of date. These two repair that.
+
+    // Recompute is_sorted from the actual data
+    pub fn recheck_sorted(&mut self) {
+        self.is_sorted = is_sorted_slice(&self.sv);
+        self.length = self.sv.len();
+    }
+
+    // Sort if the data isn't actually sorted. This checks the data
+    // rather than trusting the flag.
+    pub fn ensure_sorted(&mut self) {
+        self.recheck_sorted();
+        if !self.is_sorted {
+            self.sv.sort();
+            self.is_sorted = true;
+        }
+    }
+
+    // Split into (elements < pivot, elements >= pivot)
+    pub fn split_at_value(
+        mut self,
+        pivot: &T,
+    ) -> (SortedVector<T>, SortedVector<T>) {
+        self.ensure_sorted();
+        let split = self.sv.partition_point(|x| x < pivot);
+        let upper = self.sv.split_off(split);
+        let lower = self.sv;
+        (
+            SortedVector { length: lower.len(), sv: lower, is_sorted: true },
+            SortedVector { length: upper.len(), sv: upper, is_sorted: true },
+        )
+    }
+
+    pub fn insert(&mut self, value: T) {
+        self.ensure_sorted();
+        sorted_insert(&mut self.sv, value);
+        self.length += 1;
+    }
+
+    // Insert many items at once: sort just the new items, then do one
+    // merge pass, O(n + k log k) instead of k separate inserts.
+    pub fn insert_all(&mut self, mut items: Vec<T>) {
+        self.ensure_sorted();
+        items.sort();
+        let old = std::mem::take(&mut self.sv);
+        let mut merged = Vec::with_capacity(old.len() + items.len());
+        let mut old = old.into_iter().peekable();
+        let mut items = items.into_iter().peekable();
+        while let (Some(a), Some(b)) = (old.peek(), items.peek()) {
+            if a <= b {
+                merged.push(old.next().unwrap());
+            } else {
+                merged.push(items.next().unwrap());
+            }
+        }
+        merged.extend(old);
+        merged.extend(items);
+        self.length = merged.len();
+        self.sv = merged;
+        self.is_sorted = true;
+    }
+
+    // How many elements x with low <= x <= high. When sorted, two
+    // binary searches, so O(log n). Otherwise we just count, which at
+    // O(n) is still cheaper than sorting a copy.
+    pub fn count_in_range(&self, low: &T, high: &T) -> usize {
+        if low > high {
+            return 0;
+        }
+        if !self.is_sorted {
+            return self.sv.iter().filter(|&x| low <= x && x <= high).count();
+        }
+        let start = self.sv.partition_point(|x| x < low);
+        let end = self.sv.partition_point(|x| x <= high);
+        end - start
+    }
+
+    // References to the elements in sorted order. Unlike sorted_data
+    // (the Cow copy further down), this doesn't need T: Clone.
+    fn sorted_refs(&self) -> Vec<&T> {
+        let mut refs: Vec<&T> = self.sv.iter().collect();
+        if !self.is_sorted {
+            refs.sort();
+        }
+        refs
+    }
+
+    // Is every element of self also in other? With both sorted, we
+    // only ever move forward through other: O(n + m) once sorted.
+    pub fn is_subset(&self, other: &SortedVector<T>) -> bool {
+        let mut rest = other.sorted_refs().into_iter().peekable();
+        self.sorted_refs().into_iter().all(|x| {
+            while rest.next_if(|y| *y < x).is_some() {}
+            rest.peek() == Some(&x)
+        })
+    }
+
+    // Indices of every element equal to value: from the lower bound
+    // (first x >= value) up to the upper bound (first x > value).
+    // If value isn't there, the range is empty, and its start is where
+    // value would be inserted.
+    // The indices are only meaningful if sv is sorted, so like insert
+    // this takes &mut self and sorts first if it has to.
+    pub fn equal_range(&mut self, value: &T) -> std::ops::Range<usize> {
+        self.ensure_sorted();
+        let start = self.sv.partition_point(|x| x < value);
+        let end = start + self.sv[start..].partition_point(|x| x == value);
+        start..end
+    }
+
+    // The element closest to value. Ord alone can't say how far apart
+    // two elements are, so we also need Distance (below): we find the
+    // neighbors on each side (the largest x <= value and the smallest
+    // x >= value) and compare how far each is from value.
+    // On a tie the smaller element wins, so 25 in [10, 20, 30] gives 20.
+    pub fn nearest(&self, value: &T) -> Option<&T>
+    where
+        T: Distance,
+    {
+        let (below, above) = if self.is_sorted {
+            let pos = self.sv.partition_point(|x| x < value);
+            (pos.checked_sub(1).map(|i| &self.sv[i]), self.sv.get(pos))
+        } else {
+            (
+                self.sv.iter().filter(|&x| x <= value).max(),
+                self.sv.iter().filter(|&x| x >= value).min(),
+            )
+        };
+        match (below, above) {
+            (Some(b), Some(a)) if a.distance(value) < b.distance(value) => {
+                Some(a)
+            }
+            (Some(b), _) => Some(b),
+            (None, a) => a,
+        }
+    }
+
+    // The k-th smallest element (from 0), without a full sort:
+    // select_nth_unstable is O(n) on average. It moves k into place but
+    // leaves the rest only partly ordered, so is_sorted must end up
+    // false. If we're already sorted, the answer is just sv[k] and
+    // nothing moves.
+    pub fn select_kth(&mut self, k: usize) -> Option<&T> {
+        if k >= self.sv.len() {
+            return None;
+        }
+        if !self.is_sorted {
+            self.sv.select_nth_unstable(k);
+        }
+        Some(&self.sv[k])
+    }
+}
+
+#[test]
+fn test_recheck_sorted() {
+    let mut v = SortedVector::from_unsorted(vec![1, 2, 3]);
+    v.sv.push(0);
+    // The flag is now wrong...
+    assert!(v.is_sorted);
+    v.recheck_sorted();
+    assert!(!v.is_sorted);
+    assert_eq!(v.length, 4);
+
+    v.ensure_sorted();
+    assert!(v.is_sorted);
+    assert_eq!(v.sv, vec![0, 1, 2, 3]);
+
+    // new() doesn't sort, but recheck notices sorted data
+    let mut w = SortedVector::new(vec![1, 2]);
+    w.recheck_sorted();
+    assert!(w.is_sorted);
+}
+
+#[test]
+fn test_split_at_value() {
+    let v = SortedVector::new(vec![4, 1, 3, 5, 2]);
+    let (lower, upper) = v.split_at_value(&3);
+    assert_eq!(lower.sv, vec![1, 2]);
+    assert_eq!(upper.sv, vec![3, 4, 5]);
+    assert!(lower.is_sorted && upper.is_sorted);
+    assert_eq!((lower.length, upper.length), (2, 3));
+}
+
+#[test]
+fn test_insert() {
+    let mut v = SortedVector::new(vec![3, 1]);
+    v.insert(2);
+    assert_eq!(v.sv, vec![1, 2, 3]);
+    assert_eq!(v.length, 3);
+}
+
+#[test]
+fn test_insert_all() {
+    let mut v = SortedVector::new(vec![2, 4]);
+    v.insert_all(vec![5, 1, 3]);
+    assert_eq!(v.sv, vec![1, 2, 3, 4, 5]);
+    assert_eq!(v.length, 5);
+    assert!(v.is_sorted);
+}
+
+#[test]
+fn test_count_in_range() {
+    let v = SortedVector::from_unsorted(vec![1, 2, 3, 4, 5]);
+    assert_eq!(v.count_in_range(&2, &4), 3);
+    assert_eq!(v.count_in_range(&0, &10), 5);
+    assert_eq!(v.count_in_range(&4, &2), 0);
+    let unsorted = SortedVector::new(vec![5, 1, 4, 2, 3]);
+    assert_eq!(unsorted.count_in_range(&2, &4), 3);
+}
+
+#[test]
+fn test_is_subset() {
+    let big = SortedVector::from_unsorted(vec![1, 2, 3, 4]);
+    assert!(SortedVector::from_unsorted(vec![2, 3]).is_subset(&big));
+    assert!(!SortedVector::from_unsorted(vec![2, 5]).is_subset(&big));
+    // new() doesn't sort, but the answer is the same
+    let same = SortedVector::new(vec![3, 1, 2]);
+    assert!(same.is_subset(&SortedVector::new(vec![2, 3, 1])));
+    assert!(!SortedVector::new(vec![5, 2]).is_subset(&same));
+}
+
+#[test]
+fn test_equal_range() {
+    let mut v = SortedVector::from_unsorted(vec![1, 2, 2, 2, 3]);
+    assert_eq!(v.equal_range(&2), 1..4);
+    assert_eq!(v.equal_range(&1), 0..1);
+    assert!(v.equal_range(&5).is_empty());
+    assert_eq!(v.equal_range(&5), 5..5);
+    // new() doesn't sort, so equal_range does, and the indices are
+    // valid for sv afterwards
+    let mut unsorted = SortedVector::new(vec![2, 3, 2, 1, 2]);
+    let range = unsorted.equal_range(&2);
+    assert_eq!(range, 1..4);
+    assert!(unsorted.is_sorted);
+    assert_eq!(&unsorted.sv[range], &[2, 2, 2]);
+    assert_eq!(unsorted.equal_range(&0), 0..0);
+}
+
+#[test]
+fn test_nearest() {
+    let v = SortedVector::from_unsorted(vec![10, 20, 30]);
+    assert_eq!(v.nearest(&23), Some(&20));
+    assert_eq!(v.nearest(&29), Some(&30));
+    assert_eq!(v.nearest(&20), Some(&20));
+    // 25 is 5 away from both: the smaller one wins
+    assert_eq!(v.nearest(&25), Some(&20));
+    assert_eq!(v.nearest(&5), Some(&10));
+    assert_eq!(v.nearest(&99), Some(&30));
+    assert_eq!(SortedVector::from_unsorted(vec![]).nearest(&1), None);
+    // new() doesn't sort, but the answers are the same
+    let unsorted = SortedVector::new(vec![30, 10, 20]);
+    assert_eq!(unsorted.nearest(&23), Some(&20));
+    assert_eq!(unsorted.nearest(&29), Some(&30));
+    assert_eq!(unsorted.nearest(&25), Some(&20));
+}
+
+#[test]
+fn test_nearest_no_overflow() {
+    // 0 - i8::MIN doesn't fit in an i8, but the distance is a u8
+    let v = SortedVector::from_unsorted(vec![i8::MIN, i8::MAX]);
+    assert_eq!(v.nearest(&0), Some(&i8::MAX));
+    assert_eq!(v.nearest(&-1), Some(&i8::MIN));
+    let w = SortedVector::from_unsorted(vec![0u8, 255]);
+    assert_eq!(w.nearest(&128), Some(&255));
+    assert_eq!(w.nearest(&127), Some(&0));
+}
+
+// How far apart two values are. Plain subtraction can overflow
+// (0 - i8::MIN is 128, too big for an i8), so distances use abs_diff,
+// which returns the unsigned type of the same size and always fits.
+pub trait Distance {
+    type Output: Ord;
+    fn distance(&self, other: &Self) -> Self::Output;
+}
+
+// The impls are all the same, so a macro writes them for us
+macro_rules! impl_distance {
+    ($($t:ty => $out:ty),*) => {
+        $(
+            impl Distance for $t {
+                type Output = $out;
+                fn distance(&self, other: &Self) -> $out {
+                    self.abs_diff(*other)
+                }
+            }
+        )*
+    };
+}
+
+impl_distance!(
+    u8 => u8, u16 => u16, u32 => u32, u64 => u64, usize => usize,
+    i8 => u8, i16 => u16, i32 => u32, i64 => u64, isize => usize
+);
+
+#[test]
+fn test_select_kth() {
+    let data = vec![7, 2, 9, 4, 4, 1, 8];
+    let mut expected = data.clone();
+    expected.sort();
+    for (k, want) in expected.iter().enumerate() {
+        let mut v = SortedVector::new(data.clone());
+        assert_eq!(v.select_kth(k), Some(want));
+        assert!(!v.is_sorted);
+    }
+    let mut v = SortedVector::new(data);
+    assert_eq!(v.select_kth(7), None);
+    let mut sorted = SortedVector::from_unsorted(vec![3, 1, 2]);
+    assert_eq!(sorted.select_kth(1), Some(&2));
+    assert!(sorted.is_sorted);
+}
+
+// Collecting into Result<Vec<T>, E> stops at the first Err,
+// and ? forwards that error straight out of the function.
+pub fn try_sorted_vector<T: Ord, E, I: IntoIterator<Item = Result<T, E>>>(
+    iter: I,
+) -> Result<SortedVector<T>, E> {
+    let sv = iter.into_iter().collect::<Result<Vec<T>, E>>()?;
+    Ok(SortedVector::from_unsorted(sv))
+}
+
+#[test]
+fn test_try_sorted_vector() {
+    let v = try_sorted_vector(
+        vec!["3", "1", "2"].into_iter().map(str::parse::<i32>),
+    )
+    .unwrap();
+    assert_eq!(v.sv, vec![1, 2, 3]);
+    assert!(v.is_sorted);
+
+    let bad = try_sorted_vector(
+        vec!["3", "one", "2"].into_iter().map(str::parse::<i32>),
+    );
+    assert!(bad.is_err());
+}
+
+// Keep only the Some results of f, sorted
+pub fn filter_map_sorted<A, B: Ord, F: Fn(&A) -> Option<B>>(
+    items: &[A],
+    f: F,
+) -> SortedVector<B> {
+    SortedVector::from_unsorted(items.iter().filter_map(f).collect())
+}
+
+#[test]
+fn test_filter_map_sorted() {
+    let raw = ["30", "n/a", "26", "", "41"];
+    let ages = filter_map_sorted(&raw, |s| s.parse::<u8>().ok());
+    assert_eq!(ages.sv, vec![26, 30, 41]);
+    assert!(ages.is_sorted);
+}
+
+// Set operations. With both sides sorted, one left-to-right pass
+// over each (two pointers) is enough. A side that isn't sorted yet
+// (say, from new()) is sorted into a copy first.
+// Duplicates are dropped from the output.
+impl<T: Ord + Clone> SortedVector<T> {
+    // Only pay for a sorted copy if we are not sorted already
+    fn sorted_data(&self) -> Cow<'_, [T]> {
+        if self.is_sorted {
+            Cow::Borrowed(&self.sv)
+        } else {
+            let mut copy = self.sv.clone();
+            copy.sort();
+            Cow::Owned(copy)
+        }
+    }
+
+    pub fn intersection(&self, other: &SortedVector<T>) -> SortedVector<T> {
+        let (a, b) = (self.sorted_data(), other.sorted_data());
+        let mut result: Vec<T> = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() && j < b.len() {
+            match a[i].cmp(&b[j]) {
+                Ordering::Less => i += 1,
+                Ordering::Greater => j += 1,
+                Ordering::Equal => {
+                    if result.last() != Some(&a[i]) {
+                        result.push(a[i].clone());
+                    }
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+        SortedVector { length: result.len(), sv: result, is_sorted: true }
+    }
+
+    pub fn union(&self, other: &SortedVector<T>) -> SortedVector<T> {
+        let (a, b) = (self.sorted_data(), other.sorted_data());
+        let mut result: Vec<T> = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() || j < b.len() {
+            let next = if j == b.len() || (i < a.len() && a[i] <= b[j]) {
+                i += 1;
+                &a[i - 1]
+            } else {
+                j += 1;
+                &b[j - 1]
+            };
+            if result.last() != Some(next) {
+                result.push(next.clone());
+            }
+        }
+        SortedVector { length: result.len(), sv: result, is_sorted: true }
+    }
+
+    // Elements of self that do not appear in other
+    pub fn difference(&self, other: &SortedVector<T>) -> SortedVector<T> {
+        let (a, b) = (self.sorted_data(), other.sorted_data());
+        let mut result: Vec<T> = Vec::new();
+        let mut j = 0;
+        for x in a.iter() {
+            while j < b.len() && b[j] < *x {
+                j += 1;
+            }
+            let in_other = j < b.len() && b[j] == *x;
+            if !in_other && result.last() != Some(x) {
+                result.push(x.clone());
+            }
+        }
+        SortedVector { length: result.len(), sv: result, is_sorted: true }
+    }
+}
+
+#[test]
+fn test_intersection() {
+    let a = SortedVector::from_unsorted(vec![1, 2, 3]);
+    let b = SortedVector::from_unsorted(vec![2, 3, 4]);
+    assert_eq!(a.intersection(&b).sv, vec![2, 3]);
+}
+
+#[test]
+fn test_union() {
+    let a = SortedVector::from_unsorted(vec![1, 2, 3]);
+    let b = SortedVector::from_unsorted(vec![2, 3, 4]);
+    assert_eq!(a.union(&b).sv, vec![1, 2, 3, 4]);
+    let c = SortedVector::from_unsorted(vec![1, 1, 5]);
+    assert_eq!(a.union(&c).sv, vec![1, 2, 3, 5]);
+}
+
+#[test]
+fn test_set_operations_unsorted() {
+    // new() doesn't sort
+    let a = SortedVector::new(vec![3, 1, 2]);
+    let b = SortedVector::new(vec![2, 3, 1]);
+    let both = a.intersection(&b);
+    assert_eq!(both.sv, vec![1, 2, 3]);
+    assert!(both.is_sorted);
+    let c = SortedVector::new(vec![5, 1]);
+    assert_eq!(a.union(&c).sv, vec![1, 2, 3, 5]);
+}
+
+#[test]
+fn test_difference() {
+    let a = SortedVector::from_unsorted(vec![1, 2, 3, 4]);
+    let b = SortedVector::from_unsorted(vec![2, 4]);
+    assert_eq!(a.difference(&b).sv, vec![1, 3]);
+    assert!(b.difference(&a).sv.is_empty());
+    // Unsorted input (new() doesn't sort) gives the same answer
+    let c = SortedVector::new(vec![4, 3, 2, 1]);
+    let d = SortedVector::new(vec![4, 2]);
+    assert_eq!(c.difference(&d).sv, vec![1, 3]);
+}
+
 // Can we generalize our AddressBook example?
 
 // Capital letters for types
@@ -102,6 +658,441 @@ pub fn get_first<T>(list: &[T]) -> Option<&T> {
     }
 }
 
+// Works for any type we can use as a HashMap key.
+// On a tie, the value seen first in the slice wins.
+pub fn most_frequent<T: Eq + Hash + Clone>(items: &[T]) -> Option<T> {
+    let mut counts: HashMap<&T, usize> = HashMap::new();
+    for item in items {
+        *counts.entry(item).or_insert(0) += 1;
+    }
+    let mut best: Option<(&T, usize)> = None;
+    for item in items {
+        let count = counts[item];
+        if best.is_none_or(|(_, best_count)| count > best_count) {
+            best = Some((item, count));
+        }
+    }
+    best.map(|(item, _)| item.clone())
+}
+
+#[test]
+fn test_most_frequent() {
+    assert_eq!(most_frequent(&[1, 1, 2, 3, 1]), Some(1));
+    assert_eq!(most_frequent(&["b", "a", "a", "b"]), Some("b"));
+    assert_eq!(most_frequent::<i32>(&[]), None);
+}
+
+// Only PartialOrd, so this works on floats too. The catch is NaN:
+// every comparison with NaN is false, so a NaN never replaces the current
+// best, and a NaN in position 0 is never replaced. On ties, first wins.
+pub fn argmax<T: PartialOrd>(items: &[T]) -> Option<usize> {
+    let mut best = 0;
+    for (i, item) in items.iter().enumerate().skip(1) {
+        if *item > items[best] {
+            best = i;
+        }
+    }
+    if items.is_empty() {
+        None
+    } else {
+        Some(best)
+    }
+}
+
+#[test]
+fn test_argmax() {
+    assert_eq!(argmax(&[3.0, 1.0, 5.0, 5.0]), Some(2));
+    assert_eq!(argmax(&[1.0, f64::NAN, 2.0]), Some(2));
+    assert_eq!(argmax::<i32>(&[]), None);
+}
+
+// Drop repeats, keeping the first occurrence of each value in place.
+// Unlike Vec::dedup, the input doesn't need to be sorted.
+pub fn unique<T: Eq + Hash + Clone>(items: &[T]) -> Vec<T> {
+    let mut seen = HashSet::new();
+    items.iter().filter(|item| seen.insert(*item)).cloned().collect()
+}
+
+#[test]
+fn test_unique() {
+    assert_eq!(unique(&[3, 1, 3, 2, 1]), vec![3, 1, 2]);
+}
+
+// Union of a and b (order kept, duplicates dropped), plus how many
+// distinct values showed up in both.
+pub fn merge_counting<T: Eq + Hash + Clone>(
+    a: Vec<T>,
+    b: Vec<T>,
+) -> (Vec<T>, usize) {
+    let collisions = {
+        let in_a: HashSet<&T> = a.iter().collect();
+        unique(&b).iter().filter(|x| in_a.contains(x)).count()
+    };
+    let mut all = a;
+    all.extend(b);
+    (unique(&all), collisions)
+}
+
+#[test]
+fn test_merge_counting() {
+    let (merged, collisions) =
+        merge_counting(vec![1, 2, 3, 2], vec![3, 4, 2, 3]);
+    assert_eq!(merged, vec![1, 2, 3, 4]);
+    assert_eq!(collisions, 2);
+}
+
+// Insert into an already-sorted Vec so that it stays sorted.
+// binary_search gives Err(position) when the value is missing, which is
+// exactly where it belongs; if it's present, either answer is fine.
+pub fn sorted_insert<T: Ord>(vec: &mut Vec<T>, value: T) {
+    let pos = match vec.binary_search(&value) {
+        Ok(pos) | Err(pos) => pos,
+    };
+    vec.insert(pos, value);
+}
+
+#[test]
+fn test_sorted_insert() {
+    let mut v = vec![2, 4, 6];
+    sorted_insert(&mut v, 5);
+    sorted_insert(&mut v, 1);
+    sorted_insert(&mut v, 7);
+    sorted_insert(&mut v, 4);
+    assert_eq!(v, vec![1, 2, 4, 4, 5, 6, 7]);
+}
+
+// Non-decreasing order. Empty and one-element slices count as sorted.
+pub fn is_sorted_slice<T: Ord>(items: &[T]) -> bool {
+    items.windows(2).all(|pair| pair[0] <= pair[1])
+}
+
+#[test]
+fn test_is_sorted_slice() {
+    assert!(is_sorted_slice(&[1, 2, 2, 5]));
+    assert!(!is_sorted_slice(&[1, 3, 2]));
+    assert!(is_sorted_slice::<i32>(&[]));
+    assert!(is_sorted_slice(&[42]));
+}
+
+// Vec::dedup_by_key only removes *consecutive* duplicates, so this
+// removes every duplicate key only if items is already sorted by key.
+// Vec's version hands the closure a &mut T; ours only needs a &T.
+pub fn dedup_by_key_sorted<T, K: PartialEq, F: FnMut(&T) -> K>(
+    items: &mut Vec<T>,
+    mut key: F,
+) {
+    items.dedup_by_key(|item| key(item));
+}
+
+#[test]
+fn test_dedup_by_key_sorted() {
+    // (name, age), sorted by age: keep the first person of each age
+    let mut people =
+        vec![("erin", 18), ("caleb", 26), ("alice", 26), ("bob", 30)];
+    dedup_by_key_sorted(&mut people, |&(_, age)| age);
+    assert_eq!(people, vec![("erin", 18), ("caleb", 26), ("bob", 30)]);
+
+    let mut v = SortedVector::from_unsorted(vec![1, 2, 2, 3, 3, 3]);
+    v.dedup_by_key(|&x| x);
+    assert_eq!(v.sv, vec![1, 2, 3]);
+    assert_eq!(v.length, 3);
+}
+
+// Dictionary order: the first position where the slices differ decides.
+// If one runs out first, it's a prefix of the other, and so it's less.
+// (Slices already implement Ord this way; this spells it out.)
+pub fn lex_cmp<T: Ord>(a: &[T], b: &[T]) -> Ordering {
+    for (x, y) in a.iter().zip(b.iter()) {
+        match x.cmp(y) {
+            Ordering::Equal => {}
+            unequal => return unequal,
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+#[test]
+fn test_lex_cmp() {
+    assert_eq!(lex_cmp(&[1, 2], &[1, 2, 3]), Ordering::Less);
+    assert_eq!(lex_cmp(&[1, 3], &[1, 2]), Ordering::Greater);
+    assert_eq!(lex_cmp(&[1, 2], &[1, 2]), Ordering::Equal);
+    assert_eq!(lex_cmp::<i32>(&[], &[]), Ordering::Equal);
+    assert_eq!(lex_cmp(&[2], &[1, 9, 9]), Ordering::Greater);
+}
+
+// Two type parameters: T for the items, F for the closure.
+// Every closure has its own unique type, so F has to be generic too.
+pub fn partition<T: Clone, F: Fn(&T) -> bool>(
+    items: &[T],
+    pred: F,
+) -> (Vec<T>, Vec<T>) {
+    let mut matching = Vec::new();
+    let mut rest = Vec::new();
+    for item in items {
+        if pred(item) {
+            matching.push(item.clone());
+        } else {
+            rest.push(item.clone());
+        }
+    }
+    (matching, rest)
+}
+
+#[test]
+fn test_partition() {
+    let (even, odd) = partition(&[1, 2, 3, 4], |x| x % 2 == 0);
+    assert_eq!(even, vec![2, 4]);
+    assert_eq!(odd, vec![1, 3]);
+}
+
+// Group owned items under the key that `key` extracts from them.
+// K: Ord because BTreeMap keeps the groups sorted by key.
+pub fn group_by<T, K: Ord + Clone, F: Fn(&T) -> K>(
+    items: Vec<T>,
+    key: F,
+) -> BTreeMap<K, Vec<T>> {
+    let mut groups: BTreeMap<K, Vec<T>> = BTreeMap::new();
+    for item in items {
+        groups.entry(key(&item)).or_default().push(item);
+    }
+    groups
+}
+
+#[test]
+fn test_group_by() {
+    let groups = group_by(vec![1, 2, 3, 4, 5], |x| x % 2 == 0);
+    assert_eq!(groups[&false], vec![1, 3, 5]);
+    assert_eq!(groups[&true], vec![2, 4]);
+}
+
+// Map every item, but give up at the first error: ? returns it
+// from try_map right away, skipping the rest of the items.
+pub fn try_map<A, B, E, F: Fn(&A) -> Result<B, E>>(
+    items: &[A],
+    f: F,
+) -> Result<Vec<B>, E> {
+    let mut result = Vec::with_capacity(items.len());
+    for item in items {
+        result.push(f(item)?);
+    }
+    Ok(result)
+}
+
+#[test]
+fn test_try_map() {
+    let parsed = try_map(&["1", "2", "3"], |s| s.parse::<i32>());
+    assert_eq!(parsed, Ok(vec![1, 2, 3]));
+
+    let parsed = try_map(&["1", "2", "three", "4"], |s| s.parse::<i32>());
+    assert!(parsed.is_err());
+}
+
+// The opposite of try_map: run f on every item no matter what, and
+// report every failure along with the index of the item that caused it.
+pub fn apply_collecting_errors<T, E, F: Fn(&T) -> Result<(), E>>(
+    items: &[T],
+    f: F,
+) -> Vec<(usize, E)> {
+    items
+        .iter()
+        .enumerate()
+        .filter_map(|(i, item)| f(item).err().map(|e| (i, e)))
+        .collect()
+}
+
+#[test]
+fn test_apply_collecting_errors() {
+    let check = |x: &i32| if x % 2 == 0 { Ok(()) } else { Err(*x) };
+    let errors = apply_collecting_errors(&[2, 3, 4, 5], check);
+    assert_eq!(errors, vec![(1, 3), (3, 5)]);
+    assert!(apply_collecting_errors(&[2, 4], check).is_empty());
+}
+
+// Option implements IntoIterator (zero or one items),
+// so flatten just drops the Nones.
+pub fn flatten_options<T>(items: Vec<Option<T>>) -> Vec<T> {
+    items.into_iter().flatten().collect()
+}
+
+// Unlike try_map, keep going: sort the results into (oks, errs)
+pub fn collect_oks<T, E>(items: Vec<Result<T, E>>) -> (Vec<T>, Vec<E>) {
+    let mut oks = Vec::new();
+    let mut errs = Vec::new();
+    for item in items {
+        match item {
+            Ok(x) => oks.push(x),
+            Err(e) => errs.push(e),
+        }
+    }
+    (oks, errs)
+}
+
+#[test]
+fn test_flatten_options() {
+    assert_eq!(flatten_options(vec![Some(1), None, Some(3), None]), vec![1, 3]);
+    assert!(flatten_options::<i32>(vec![None, None]).is_empty());
+}
+
+#[test]
+fn test_collect_oks() {
+    let items = vec![Ok(1), Err("bad"), Ok(3), Err("worse")];
+    let (oks, errs) = collect_oks(items);
+    assert_eq!(oks, vec![1, 3]);
+    assert_eq!(errs, vec!["bad", "worse"]);
+}
+
+// Operator traits as bounds: T: Add<Output = T> is what lets us write +
+// Default gives us a "zero" to start from.
+pub fn scan_sum<T: Copy + Add<Output = T> + Default>(items: &[T]) -> Vec<T> {
+    let mut total = T::default();
+    items
+        .iter()
+        .map(|&x| {
+            total = total + x;
+            total
+        })
+        .collect()
+}
+
+#[test]
+fn test_scan_sum() {
+    assert_eq!(scan_sum(&[1, 2, 3]), vec![1, 3, 6]);
+    assert_eq!(scan_sum::<f64>(&[]), Vec::<f64>::new());
+}
+
+// Like scan_sum, but for any state: f sees the state so far and the
+// next item, and returns the new state plus what to emit for that item.
+// We get back both the final state and everything emitted.
+pub fn fold_map<T, S: Clone, B, F: FnMut(&S, &T) -> (S, B)>(
+    items: &[T],
+    init: S,
+    mut f: F,
+) -> (S, Vec<B>) {
+    let mut state = init;
+    let mut mapped = Vec::with_capacity(items.len());
+    for item in items {
+        let (next, out) = f(&state, item);
+        state = next;
+        mapped.push(out);
+    }
+    (state, mapped)
+}
+
+#[test]
+fn test_fold_map() {
+    // The state is the prefix sum; we emit each item with its running total
+    let (total, running) =
+        fold_map(&[1, 2, 3, 4], 0, |&sum, &x| (sum + x, (x, sum + x)));
+    assert_eq!(total, 10);
+    assert_eq!(running, vec![(1, 1), (2, 3), (3, 6), (4, 10)]);
+    let (total, running) = fold_map(&[], 7, |&sum, &x: &i32| (sum + x, x));
+    assert_eq!(total, 7);
+    assert!(running.is_empty());
+}
+
+// Alternate a[0], b[0], a[1], b[1], ... then whatever is left over
+pub fn interleave<T: Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    let mut result = Vec::with_capacity(a.len() + b.len());
+    let shared = a.len().min(b.len());
+    for (x, y) in a.iter().zip(b.iter()) {
+        result.push(x.clone());
+        result.push(y.clone());
+    }
+    result.extend_from_slice(&a[shared..]);
+    result.extend_from_slice(&b[shared..]);
+    result
+}
+
+#[test]
+fn test_interleave_equal() {
+    assert_eq!(interleave(&[1, 3], &[2, 4]), vec![1, 2, 3, 4]);
+}
+
+#[test]
+fn test_interleave_unequal() {
+    assert_eq!(interleave(&[1, 3, 5], &[2, 4]), vec![1, 2, 3, 4, 5]);
+    assert_eq!(interleave(&[1], &[2, 4, 6]), vec![1, 2, 4, 6]);
+}
+
+// Three independent type parameters: the slices don't have to agree.
+// Stops at the shortest slice, just like Iterator::zip.
+pub fn zip3<A: Clone, B: Clone, C: Clone>(
+    a: &[A],
+    b: &[B],
+    c: &[C],
+) -> Vec<(A, B, C)> {
+    a.iter()
+        .zip(b)
+        .zip(c)
+        .map(|((x, y), z)| (x.clone(), y.clone(), z.clone()))
+        .collect()
+}
+
+#[test]
+fn test_zip3() {
+    let zipped = zip3(&[1, 2, 3], &["a", "b"], &[true, false, true, false]);
+    assert_eq!(zipped, vec![(1, "a", true), (2, "b", false)]);
+}
+
+// The same signature as the slice method: F tells us whether the element
+// it is given is Less/Equal/Greater than what we are looking for.
+// Ok(index) if found, Err(index where it would be inserted) otherwise.
+pub fn binary_search_by<T, F: Fn(&T) -> Ordering>(
+    slice: &[T],
+    f: F,
+) -> Result<usize, usize> {
+    slice.binary_search_by(f)
+}
+
+#[test]
+fn test_binary_search_by() {
+    struct Student {
+        id: u32,
+        name: &'static str,
+    }
+    let students = [
+        Student { id: 3, name: "alice" },
+        Student { id: 7, name: "bob" },
+        Student { id: 12, name: "caleb" },
+    ];
+    let found = binary_search_by(&students, |s| s.id.cmp(&7));
+    assert_eq!(found, Ok(1));
+    assert_eq!(students[found.unwrap()].name, "bob");
+    assert_eq!(binary_search_by(&students, |s| s.id.cmp(&10)), Err(2));
+}
+
+// Look up key in the cache; only call f (possibly expensive) on a miss.
+// The caller owns the cache, so it can be reused across calls.
+pub fn memoize<A: Eq + Hash + Clone, B: Clone, F: Fn(&A) -> B>(
+    cache: &mut HashMap<A, B>,
+    key: A,
+    f: F,
+) -> B {
+    if let Some(value) = cache.get(&key) {
+        return value.clone();
+    }
+    let value = f(&key);
+    cache.insert(key, value.clone());
+    value
+}
+
+#[test]
+fn test_memoize() {
+    use std::cell::Cell;
+    // f is only Fn, so count calls through a Cell
+    let calls = Cell::new(0);
+    let square = |x: &u64| {
+        calls.set(calls.get() + 1);
+        x * x
+    };
+    let mut cache = HashMap::new();
+    assert_eq!(memoize(&mut cache, 12, square), 144);
+    assert_eq!(memoize(&mut cache, 12, square), 144);
+    assert_eq!(calls.get(), 1);
+    assert_eq!(memoize(&mut cache, 3, square), 9);
+    assert_eq!(calls.get(), 2);
+}
+
 /*
     Generic Trait Bounds
 
@@ -121,6 +1112,189 @@ pub fn print_vec<T: Debug>(v: &[T]) {
     }
 }
 
+// Here the bound is Clone: we copy the elements out into new Vecs.
+// The last chunk may be shorter; size 0 gives no chunks at all.
+pub fn chunk<T: Clone>(items: &[T], size: usize) -> Vec<Vec<T>> {
+    if size == 0 {
+        return Vec::new();
+    }
+    items.chunks(size).map(|c| c.to_vec()).collect()
+}
+
+#[test]
+fn test_chunk_even() {
+    assert_eq!(chunk(&[1, 2, 3, 4], 2), vec![vec![1, 2], vec![3, 4]]);
+    assert!(chunk(&[1, 2, 3, 4], 0).is_empty());
+}
+
+#[test]
+fn test_chunk_uneven() {
+    assert_eq!(
+        chunk(&[1, 2, 3, 4, 5], 2),
+        vec![vec![1, 2], vec![3, 4], vec![5]]
+    );
+}
+
+// Rows become columns. The input must be rectangular: this panics
+// if the rows have different lengths.
+// We move the elements out of the rows, so T doesn't even need Clone.
+pub fn transpose<T>(rows: Vec<Vec<T>>) -> Vec<Vec<T>> {
+    let width = rows.first().map_or(0, Vec::len);
+    assert!(
+        rows.iter().all(|row| row.len() == width),
+        "transpose: ragged input, every row must have {} elements",
+        width
+    );
+    let mut rows: Vec<_> = rows.into_iter().map(Vec::into_iter).collect();
+    (0..width)
+        .map(|_| rows.iter_mut().map(|row| row.next().unwrap()).collect())
+        .collect()
+}
+
+#[test]
+fn test_transpose() {
+    let m = vec![vec![1, 2, 3], vec![4, 5, 6]];
+    assert_eq!(transpose(m), vec![vec![1, 4], vec![2, 5], vec![3, 6]]);
+    assert!(transpose::<i32>(vec![]).is_empty());
+}
+
+#[test]
+#[should_panic(expected = "ragged")]
+fn test_transpose_ragged() {
+    transpose(vec![vec![1, 2], vec![3]]);
+}
+
+// The opposite of chunk. Taking the Vec<Vec<T>> by value lets us move
+// the elements; flatten_ref only borrows, so it has to clone them.
+pub fn flatten<T: Clone>(nested: Vec<Vec<T>>) -> Vec<T> {
+    nested.into_iter().flatten().collect()
+}
+
+pub fn flatten_ref<T: Clone>(nested: &[Vec<T>]) -> Vec<T> {
+    nested.iter().flatten().cloned().collect()
+}
+
+#[test]
+fn test_flatten() {
+    let nested = vec![vec![1, 2], vec![], vec![3]];
+    assert_eq!(flatten_ref(&nested), vec![1, 2, 3]);
+    assert_eq!(flatten(nested), vec![1, 2, 3]);
+    assert!(flatten::<i32>(vec![]).is_empty());
+}
+
+// [1, 1, 2, 3, 3, 3] -> [(1, 2), (2, 1), (3, 3)]
+// Only needs PartialEq: we compare neighbors, we never sort.
+pub fn run_length_encode<T: PartialEq + Clone>(items: &[T]) -> Vec<(T, usize)> {
+    let mut runs: Vec<(T, usize)> = Vec::new();
+    for item in items {
+        match runs.last_mut() {
+            Some((value, count)) if value == item => *count += 1,
+            _ => runs.push((item.clone(), 1)),
+        }
+    }
+    runs
+}
+
+pub fn run_length_decode<T: Clone>(runs: &[(T, usize)]) -> Vec<T> {
+    let mut items = Vec::new();
+    for (value, count) in runs {
+        items.extend(std::iter::repeat_n(value.clone(), *count));
+    }
+    items
+}
+
+#[test]
+fn test_run_length_round_trip() {
+    let items = [1, 1, 2, 3, 3, 3];
+    let runs = run_length_encode(&items);
+    assert_eq!(runs, vec![(1, 2), (2, 1), (3, 3)]);
+    assert_eq!(run_length_decode(&runs), items);
+}
+
+// The max of every window of `window` neighbors, in O(n): the deque
+// holds indices whose values are decreasing, so the front is always the
+// current max. A window of 0, or longer than the input, gives nothing.
+pub fn sliding_max<T: Ord + Copy>(items: &[T], window: usize) -> Vec<T> {
+    if window == 0 || window > items.len() {
+        return Vec::new();
+    }
+    let mut maxes = Vec::with_capacity(items.len() - window + 1);
+    let mut candidates: VecDeque<usize> = VecDeque::new();
+    for (i, &item) in items.iter().enumerate() {
+        // Anything smaller than the new item can never be a max again
+        while candidates.back().is_some_and(|&j| items[j] <= item) {
+            candidates.pop_back();
+        }
+        candidates.push_back(i);
+        // Drop the front once it slides out of the window
+        if candidates[0] + window <= i {
+            candidates.pop_front();
+        }
+        if i + 1 >= window {
+            maxes.push(items[candidates[0]]);
+        }
+    }
+    maxes
+}
+
+#[test]
+fn test_sliding_max() {
+    assert_eq!(sliding_max(&[1, 3, 2, 5, 4], 2), vec![3, 3, 5, 5]);
+    assert_eq!(sliding_max(&[5, 4, 3, 2, 1], 3), vec![5, 4, 3]);
+    assert!(sliding_max(&[1, 2, 3], 0).is_empty());
+    assert!(sliding_max(&[1, 2, 3], 4).is_empty());
+}
+
+// Not generic on purpose: a u8 has only 256 possible values, so we can
+// count how many of each there are and write them back out in order.
+// O(n) instead of O(n log n), which suits ages.
+pub fn counting_sort_u8(items: &[u8]) -> Vec<u8> {
+    let mut counts = [0usize; 256];
+    for &x in items {
+        counts[x as usize] += 1;
+    }
+    let mut sorted = Vec::with_capacity(items.len());
+    for (value, &count) in counts.iter().enumerate() {
+        sorted.extend(std::iter::repeat_n(value as u8, count));
+    }
+    sorted
+}
+
+#[test]
+fn test_counting_sort_u8() {
+    // A small linear congruential generator, so the test needs no rand
+    let mut seed: u32 = 198;
+    let items: Vec<u8> = (0..1000)
+        .map(|_| {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (seed >> 16) as u8
+        })
+        .collect();
+    let mut expected = items.clone();
+    expected.sort();
+    assert_eq!(counting_sort_u8(&items), expected);
+    assert!(counting_sort_u8(&[]).is_empty());
+}
+
+// A sparse histogram like AddressBook::age_histogram only has the ages
+// someone actually is. For plotting we want every age from 0 to max,
+// with 0 for the missing ones. Ages past max are left off.
+pub fn densify(hist: &BTreeMap<u8, usize>, max: u8) -> Vec<usize> {
+    let mut dense = vec![0; max as usize + 1];
+    for (&age, &count) in hist.range(..=max) {
+        dense[age as usize] = count;
+    }
+    dense
+}
+
+#[test]
+fn test_densify() {
+    let hist: BTreeMap<u8, usize> = vec![(2, 1), (5, 3)].into_iter().collect();
+    assert_eq!(densify(&hist, 5), vec![0, 0, 1, 0, 0, 3]);
+    assert_eq!(densify(&hist, 3), vec![0, 0, 1, 0]);
+    assert_eq!(densify(&BTreeMap::new(), 0), vec![0]);
+}
+
 // Similarly if we want to directly implement Debug printing
 // for a container type, like our SortedVector,
 // we can't implement it for ALL SortedVector, we have to assume
@@ -143,6 +1317,56 @@ impl<P: Ord> Into<Vec<P>> for SortedVector<P> {
     }
 }
 
+// Two SortedVectors compare by their contents in sorted order,
+// lexicographically (see lex_cmp). is_sorted and length are
+// bookkeeping, so they don't take part. A vector built with new()
+// isn't sorted yet, so we compare sorted references to its elements.
+impl<T: Ord> Ord for SortedVector<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self.is_sorted && other.is_sorted {
+            return self.sv.cmp(&other.sv);
+        }
+        let mut mine: Vec<&T> = self.sv.iter().collect();
+        let mut theirs: Vec<&T> = other.sv.iter().collect();
+        mine.sort();
+        theirs.sort();
+        mine.cmp(&theirs)
+    }
+}
+
+impl<T: Ord> PartialOrd for SortedVector<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Eq and PartialEq have to agree with Ord
+impl<T: Ord> PartialEq for SortedVector<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: Ord> Eq for SortedVector<T> {}
+
+#[test]
+fn test_sort_sorted_vectors() {
+    let mut vs = vec![
+        SortedVector::from_unsorted(vec![1, 3]),
+        SortedVector::new(vec![2, 1]),
+        SortedVector::from_unsorted(vec![1, 2, 3]),
+        SortedVector::from_unsorted(vec![]),
+    ];
+    vs.sort();
+    let contents: Vec<Vec<i32>> = vs.into_iter().map(|v| v.sv).collect();
+    assert_eq!(contents, vec![vec![], vec![2, 1], vec![1, 2, 3], vec![1, 3]]);
+    // The flag doesn't matter, only the contents
+    assert!(
+        SortedVector::new(vec![2, 1])
+            == SortedVector::from_unsorted(vec![1, 2])
+    );
+}
+
 /*
     Iterators!
     Iterators in Rust are powerful, and encapsulated by the
@@ -162,8 +1386,255 @@ pub trait Iterator {
 // Collect requires FromIterator:
 // https://doc.rust-lang.org/std/vec/struct.Vec.html#impl-FromIterator%3CT%3E
 
+// Implementing the real Iterator trait ourselves: a copy of std's chain.
+// (This file defines its own Iterator above, so we have to spell out
+// std::iter::Iterator here.)
+// Iterator adapters are lazy: nothing happens until someone calls next().
+pub struct Chain<I, J> {
+    first: I,
+    second: J,
+    first_done: bool,
+}
+
+pub fn chain<I, J>(first: I, second: J) -> Chain<I, J>
+where
+    I: std::iter::Iterator,
+    J: std::iter::Iterator<Item = I::Item>,
+{
+    Chain { first, second, first_done: false }
+}
+
+impl<I, J> std::iter::Iterator for Chain<I, J>
+where
+    I: std::iter::Iterator,
+    J: std::iter::Iterator<Item = I::Item>,
+{
+    type Item = I::Item;
+    fn next(&mut self) -> Option<I::Item> {
+        if !self.first_done {
+            match self.first.next() {
+                Some(x) => return Some(x),
+                // Don't call first.next() again once it has run out
+                None => self.first_done = true,
+            }
+        }
+        self.second.next()
+    }
+}
+
+#[test]
+fn test_chain() {
+    let chained: Vec<i32> = chain(0..3, 10..12).collect();
+    assert_eq!(chained, vec![0, 1, 2, 10, 11]);
+    assert_eq!(chain(0..0, 0..0).count(), 0);
+}
+
 // Traits can be generic too!
-trait Graph<N, E> {
+pub trait Graph<N, E> {
     fn has_edge(&self, &N, &N) -> bool;
+    fn nodes(&self) -> Vec<&N>;
+    fn neighbors(&self, node: &N) -> Vec<&N>;
     // ...
+
+    // Default methods can ask for extra bounds on the type parameters.
+    // Counts connected components, treating every edge as undirected,
+    // so an isolated node is a component on its own.
+    fn connected_components(&self) -> usize
+    where
+        N: Eq + Hash + Clone,
+    {
+        // Edges only go one way, so first collect both directions
+        let mut undirected: HashMap<N, Vec<N>> = HashMap::new();
+        for node in self.nodes() {
+            for next in self.neighbors(node) {
+                undirected.entry(node.clone()).or_default().push(next.clone());
+                undirected.entry(next.clone()).or_default().push(node.clone());
+            }
+        }
+
+        let mut visited: HashSet<N> = HashSet::new();
+        let mut components = 0;
+        for start in self.nodes() {
+            if visited.contains(start) {
+                continue;
+            }
+            components += 1;
+            visited.insert(start.clone());
+            let mut stack = vec![start.clone()];
+            while let Some(node) = stack.pop() {
+                for next in undirected.get(&node).into_iter().flatten() {
+                    if visited.insert(next.clone()) {
+                        stack.push(next.clone());
+                    }
+                }
+            }
+        }
+        components
+    }
+}
+
+// A directed graph stored as an adjacency list.
+// No data on the edges, so E is just ().
+pub struct AdjListGraph<N> {
+    pub adj: HashMap<N, Vec<N>>,
+}
+
+impl<N: Eq + Hash + Clone> AdjListGraph<N> {
+    pub fn new() -> Self {
+        AdjListGraph { adj: HashMap::new() }
+    }
+    pub fn add_node(&mut self, node: N) {
+        self.adj.entry(node).or_default();
+    }
+    // Adds the edge from -> to (and both nodes, if missing)
+    pub fn add_edge(&mut self, from: N, to: N) {
+        self.add_node(to.clone());
+        self.adj.entry(from).or_default().push(to);
+    }
+}
+
+// DFS coloring: a node is "gray" while we are still exploring
+// below it, and "black" once everything reachable from it is done.
+#[derive(Clone, Copy, PartialEq)]
+enum Color {
+    Gray,
+    Black,
+}
+
+impl<N: Eq + Hash + Clone> AdjListGraph<N> {
+    // Reaching a gray node again means we followed a back edge: a cycle.
+    pub fn has_cycle(&self) -> bool {
+        let mut colors: HashMap<&N, Color> = HashMap::new();
+        self.adj.keys().any(|node| self.visit_for_cycle(node, &mut colors))
+    }
+
+    fn visit_for_cycle<'a>(
+        &'a self,
+        node: &'a N,
+        colors: &mut HashMap<&'a N, Color>,
+    ) -> bool {
+        match colors.get(node) {
+            Some(Color::Gray) => return true,
+            Some(Color::Black) => return false,
+            None => {}
+        }
+        colors.insert(node, Color::Gray);
+        for next in self.adj.get(node).into_iter().flatten() {
+            if self.visit_for_cycle(next, colors) {
+                return true;
+            }
+        }
+        colors.insert(node, Color::Black);
+        false
+    }
+
+    // Kahn's algorithm: repeatedly take a node nobody points to anymore.
+    // If nodes are left over at the end, they must be on a cycle.
+    pub fn topo_sort(&self) -> Result<Vec<N>, String> {
+        let mut in_degree: HashMap<&N, usize> =
+            self.adj.keys().map(|node| (node, 0)).collect();
+        for next in self.adj.values().flatten() {
+            *in_degree.entry(next).or_insert(0) += 1;
+        }
+
+        let mut ready: Vec<&N> = in_degree
+            .iter()
+            .filter(|&(_, &degree)| degree == 0)
+            .map(|(&node, _)| node)
+            .collect();
+        let mut order = Vec::new();
+        while let Some(node) = ready.pop() {
+            order.push(node.clone());
+            for next in self.adj.get(node).into_iter().flatten() {
+                let degree = in_degree.get_mut(next).unwrap();
+                *degree -= 1;
+                if *degree == 0 {
+                    ready.push(next);
+                }
+            }
+        }
+
+        if order.len() == in_degree.len() {
+            Ok(order)
+        } else {
+            Err("graph has a cycle, no topological order exists".to_string())
+        }
+    }
+}
+
+impl<N: Eq + Hash + Clone> Default for AdjListGraph<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N: Eq + Hash> Graph<N, ()> for AdjListGraph<N> {
+    fn has_edge(&self, from: &N, to: &N) -> bool {
+        self.adj.get(from).is_some_and(|next| next.contains(to))
+    }
+    fn nodes(&self) -> Vec<&N> {
+        self.adj.keys().collect()
+    }
+    fn neighbors(&self, node: &N) -> Vec<&N> {
+        self.adj.get(node).map_or(Vec::new(), |next| next.iter().collect())
+    }
+}
+
+#[test]
+fn test_connected_components() {
+    let mut g = AdjListGraph::new();
+    for &(a, b) in &[(1, 2), (2, 3), (3, 1), (4, 5), (5, 6), (6, 4)] {
+        g.add_edge(a, b);
+    }
+    assert_eq!(g.connected_components(), 2);
+
+    // Isolated nodes count as their own component
+    g.add_node(7);
+    assert_eq!(g.connected_components(), 3);
+}
+
+#[test]
+fn test_has_cycle_simple() {
+    let mut g = AdjListGraph::new();
+    g.add_edge("A", "B");
+    g.add_edge("B", "A");
+    assert!(g.has_cycle());
+}
+
+#[test]
+fn test_has_cycle_dag() {
+    let mut g = AdjListGraph::new();
+    g.add_edge("A", "B");
+    g.add_edge("A", "C");
+    g.add_edge("B", "D");
+    g.add_edge("C", "D");
+    assert!(!g.has_cycle());
+}
+
+#[test]
+fn test_has_cycle_self_loop() {
+    let mut g = AdjListGraph::new();
+    g.add_edge("A", "B");
+    g.add_edge("B", "B");
+    assert!(g.has_cycle());
+}
+
+#[test]
+fn test_topo_sort_dag() {
+    let edges = [("shop", "cook"), ("cook", "eat"), ("wake", "shop")];
+    let mut g = AdjListGraph::new();
+    for &(from, to) in &edges {
+        g.add_edge(from, to);
+    }
+    let order = g.topo_sort().unwrap();
+    assert_eq!(order, vec!["wake", "shop", "cook", "eat"]);
+}
+
+#[test]
+fn test_topo_sort_cycle() {
+    let mut g = AdjListGraph::new();
+    g.add_edge("A", "B");
+    g.add_edge("B", "C");
+    g.add_edge("C", "A");
+    assert!(g.topo_sort().is_err());
 }
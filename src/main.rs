@@ -1,3 +1,10 @@
+#[cfg(feature = "rand")]
+extern crate rand;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "serde")]
+extern crate serde_json;
+
 pub mod generics;
 pub mod more_utility_traits;
 pub mod traits;
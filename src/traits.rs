@@ -7,37 +7,160 @@
     - Whole database: HashMap
 */
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt;
 use std::fmt::Display;
-use std::ops::{Deref, Index};
+use std::iter::FromIterator;
+use std::ops::{Bound, Deref, Index};
 use std::str::FromStr;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Person {
     name: String,
     age: u8,
-    phone: [u8; 10], // fixed array of 10 digits
-    favorite_color: String,
+    phone: [u8; 10],                // fixed array of 10 digits
+    favorite_color: Option<String>, // None if we don't know it
 }
 
 pub struct AddressBook {
     // Note: struct fields
     // should not usually be public, normally would want to hide implementation
     // details in the API for your data structure
-    by_name: HashMap<String, Person>,
-    by_age: HashMap<u8, Vec<Person>>,
+    // A BTreeMap keeps the names in sorted order: iterating is
+    // alphabetical and prefix searches are range queries, at the cost
+    // of O(log n) lookups instead of O(1).
+    by_name: BTreeMap<String, Person>,
+    // Also ordered, so we can ask for a range of ages
+    by_age: BTreeMap<u8, Vec<Person>>,
+    // Reverse lookup phone -> names, in the order they were added.
+    // People can share a phone, so it's a Vec like by_age. DEFAULT_PHONE
+    // is a placeholder shared by many people, so it is never indexed.
+    by_phone: HashMap<[u8; 10], Vec<String>>,
+    // Names merge_with(.., Suffix) made up -> the name the person came
+    // in with, e.g. "caleb_2" -> "caleb". Used by coalesce.
+    suffixed: BTreeMap<String, String>,
 }
 impl AddressBook {
     pub fn new() -> Self {
-        Self { by_name: HashMap::new(), by_age: HashMap::new() }
+        Self {
+            by_name: BTreeMap::new(),
+            by_age: BTreeMap::new(),
+            by_phone: HashMap::new(),
+            suffixed: BTreeMap::new(),
+        }
+    }
+    // Capacity is only a performance hint: room for n people up front
+    // means fewer reallocations while bulk loading.
+    // A BTreeMap allocates node by node and has no capacity, so now
+    // that by_name and by_age are BTreeMaps, only by_phone reserves
+    // anything (here and in reserve).
+    pub fn with_capacity(n: usize) -> Self {
+        Self {
+            by_name: BTreeMap::new(),
+            by_age: BTreeMap::new(),
+            by_phone: HashMap::with_capacity(n),
+            suffixed: BTreeMap::new(),
+        }
+    }
+    pub fn reserve(&mut self, additional: usize) {
+        self.by_phone.reserve(additional);
     }
     pub fn add_person(&mut self, person: Person) {
-        self.by_name.insert(person.name.clone(), person.clone());
-        // Should be using the entry API
-        // But I'm just illustrating here
-        self.by_age.insert(person.age, Vec::new());
-        self.by_age.get_mut(&person.age).unwrap().push(person);
+        // Adding someone with an existing name replaces them, so their
+        // old entry must also leave the other indexes.
+        if let Some(old) =
+            self.by_name.insert(person.name.clone(), person.clone())
+        {
+            self.unindex(&old);
+        }
+        if person.phone != DEFAULT_PHONE {
+            self.by_phone
+                .entry(person.phone)
+                .or_default()
+                .push(person.name.clone());
+        }
+        // The entry API creates the Vec only if this age is new
+        self.by_age.entry(person.age).or_default().push(person);
+    }
+    // Anything we can iterate over that yields People: a Vec, an array,
+    // another iterator chain, ...
+    pub fn bulk_add<I: IntoIterator<Item = Person>>(&mut self, people: I) {
+        for person in people {
+            self.add_person(person);
+        }
+    }
+    pub fn remove_person(&mut self, name: &str) -> Option<Person> {
+        let person = self.by_name.remove(name)?;
+        self.unindex(&person);
+        Some(person)
+    }
+    // If several people share the phone, the one added first
+    pub fn find_by_phone(&self, phone: &[u8; 10]) -> Option<&Person> {
+        let names = self.by_phone.get(phone)?;
+        names.first().map(|name| &self.by_name[name])
+    }
+    pub fn len(&self) -> usize {
+        self.by_name.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.by_name.is_empty()
+    }
+    pub fn contains_name(&self, name: &str) -> bool {
+        self.by_name.contains_key(name)
+    }
+    // Number of people at each age
+    pub fn age_histogram(&self) -> BTreeMap<u8, usize> {
+        self.by_age.iter().map(|(&age, people)| (age, people.len())).collect()
+    }
+    // The indexes hold copies of the same data, so they can drift apart
+    // if a method forgets to update one of them. Handy in tests.
+    pub fn is_consistent(&self) -> bool {
+        // Everyone in by_name is in their age bucket exactly once...
+        let each_person_indexed = self.by_name.values().all(|person| {
+            self.by_age.get(&person.age).is_some_and(|bucket| {
+                bucket.iter().filter(|p| *p == person).count() == 1
+            })
+        });
+        // ...and the buckets hold nobody else
+        let bucket_total: usize = self.by_age.values().map(Vec::len).sum();
+        // Same for phones: everyone with a real phone is under it once...
+        let with_phone =
+            self.by_name.values().filter(|p| p.phone != DEFAULT_PHONE);
+        let each_phone_indexed = with_phone.clone().all(|person| {
+            self.by_phone.get(&person.phone).is_some_and(|names| {
+                names.iter().filter(|n| **n == person.name).count() == 1
+            })
+        });
+        // ...and nobody else is indexed
+        let phone_total: usize = self.by_phone.values().map(Vec::len).sum();
+        let copies_present =
+            self.suffixed.keys().all(|name| self.by_name.contains_key(name));
+        each_person_indexed
+            && bucket_total == self.by_name.len()
+            && each_phone_indexed
+            && phone_total == with_phone.count()
+            && copies_present
+    }
+    // Take a person out of by_age, by_phone and suffixed, dropping any
+    // bucket that is left empty.
+    fn unindex(&mut self, person: &Person) {
+        self.suffixed.remove(&person.name);
+        if let Some(bucket) = self.by_age.get_mut(&person.age) {
+            bucket.retain(|p| p.name != person.name);
+            if bucket.is_empty() {
+                self.by_age.remove(&person.age);
+            }
+        }
+        if let Some(names) = self.by_phone.get_mut(&person.phone) {
+            names.retain(|name| *name != person.name);
+            if names.is_empty() {
+                self.by_phone.remove(&person.phone);
+            }
+        }
     }
 }
 
@@ -59,12 +182,117 @@ fn test_assert_eq_person() {
         name: "caleb".to_owned(),
         age: 26,
         phone: [5, 5, 5, 5, 5, 5, 5, 5, 5, 5],
-        favorite_color: "Purple".to_owned(),
+        favorite_color: Some("Purple".to_owned()),
     };
     let person2 = person1.clone();
     assert_eq!(person1, person2);
 }
 
+#[test]
+fn test_contains_name() {
+    let mut book = AddressBook::new();
+    book.add_person(Person::from(("caleb".to_owned(), 26)));
+    assert!(book.contains_name("caleb"));
+    assert!(!book.contains_name("alice"));
+}
+
+#[test]
+fn test_is_consistent() {
+    let mut book = AddressBook::new();
+    book.add_person(Person::from(("caleb".to_owned(), 26)));
+    book.add_person(Person::from(("alice".to_owned(), 26)));
+    book.add_person(Person::from(("caleb".to_owned(), 27)));
+    assert!(book.is_consistent());
+
+    // Reach into the index directly to break it
+    book.by_age
+        .get_mut(&26)
+        .unwrap()
+        .push(Person::from(("bob".to_owned(), 26)));
+    assert!(!book.is_consistent());
+
+    // A person missing from by_phone is caught too
+    let phone = [2, 1, 5, 8, 9, 8, 1, 2, 3, 4];
+    let mut book = AddressBook::new();
+    book.add_person(Person {
+        name: "caleb".to_owned(),
+        age: 26,
+        phone,
+        favorite_color: None,
+    });
+    assert!(book.is_consistent());
+    book.by_phone.remove(&phone);
+    assert!(!book.is_consistent());
+}
+
+#[test]
+fn test_with_capacity() {
+    let mut book = AddressBook::with_capacity(10);
+    book.reserve(5);
+    book.add_person(Person::from(("caleb".to_owned(), 26)));
+    book.add_person(Person::from(("alice".to_owned(), 26)));
+    assert_eq!(book.len(), 2);
+    assert_eq!(book["alice"].age, 26);
+    assert_eq!(book.age_histogram()[&26], 2);
+}
+
+#[test]
+fn test_bulk_add() {
+    let mut book = AddressBook::new();
+    book.bulk_add(vec![
+        Person::from(("caleb".to_owned(), 26)),
+        Person::from(("alice".to_owned(), 30)),
+        Person::from(("bob".to_owned(), 40)),
+    ]);
+    assert_eq!(book.len(), 3);
+}
+
+#[test]
+fn test_find_by_phone() {
+    let phone = [2, 1, 5, 8, 9, 8, 1, 2, 3, 4];
+    let mut book = AddressBook::new();
+    book.add_person(Person {
+        name: "caleb".to_owned(),
+        age: 26,
+        phone,
+        favorite_color: Some("Purple".to_owned()),
+    });
+    book.add_person(Person::from(("alice".to_owned(), 30)));
+    assert_eq!(book.find_by_phone(&phone).unwrap().name, "caleb");
+    // The placeholder phone is not indexed
+    assert!(book.find_by_phone(&DEFAULT_PHONE).is_none());
+
+    assert!(book.remove_person("caleb").is_some());
+    assert!(book.find_by_phone(&phone).is_none());
+}
+
+#[test]
+fn test_find_by_shared_phone() {
+    let phone = [2, 1, 5, 8, 9, 8, 1, 2, 3, 4];
+    let mut book = AddressBook::new();
+    for &name in &["caleb", "alice"] {
+        book.add_person(Person {
+            name: name.to_owned(),
+            age: 26,
+            phone,
+            favorite_color: None,
+        });
+    }
+    assert_eq!(book.find_by_phone(&phone).unwrap().name, "caleb");
+    // Removing either one leaves the other findable
+    book.remove_person("alice");
+    assert_eq!(book.find_by_phone(&phone).unwrap().name, "caleb");
+    book.add_person(Person {
+        name: "alice".to_owned(),
+        age: 26,
+        phone,
+        favorite_color: None,
+    });
+    book.remove_person("caleb");
+    assert_eq!(book.find_by_phone(&phone).unwrap().name, "alice");
+    assert!(book.is_consistent());
+}
+
 /*
     ***** Part 1 *****
     Implementing a trait for a type
@@ -154,11 +382,12 @@ impl PartialEq for Person {
 //    you can think of fmt::Result as just a customization of Result<(), String>
 impl Display for Person {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "Person with name {}, age {}, other details omitted",
-            self.name, self.age,
-        )
+        write!(f, "Person with name {}, age {}, ", self.name, self.age,)?;
+        match self.favorite_color {
+            Some(ref color) => write!(f, "favorite color {}", color)?,
+            None => write!(f, "no known favorite color")?,
+        }
+        write!(f, ", other details omitted")
     }
 }
 
@@ -168,7 +397,7 @@ fn test_display_person() {
         name: "caleb".to_owned(),
         age: 26,
         phone: [5, 5, 5, 5, 5, 5, 5, 5, 5, 5],
-        favorite_color: "Purple".to_owned(),
+        favorite_color: Some("Purple".to_owned()),
     };
     println!("{}", caleb);
     // Useful trick:
@@ -178,6 +407,24 @@ fn test_display_person() {
     // assert!(false);
 }
 
+#[test]
+fn test_favorite_color() {
+    let mut caleb = Person::from(("caleb".to_owned(), 26));
+    assert_eq!(caleb.favorite_color(), None);
+    assert_eq!(
+        caleb.to_string(),
+        "Person with name caleb, age 26, no known favorite color, \
+         other details omitted"
+    );
+    caleb.favorite_color = Some("Purple".to_owned());
+    assert_eq!(caleb.favorite_color(), Some("Purple"));
+    assert_eq!(
+        caleb.to_string(),
+        "Person with name caleb, age 26, favorite color Purple, \
+         other details omitted"
+    );
+}
+
 // Example from Piazza Q
 // If you want to re-implement Display for a type that already exists,
 // you need to make a wrapper around that type, like this:
@@ -203,12 +450,22 @@ const DEFAULT_PHONE: [u8; 10] = [5, 5, 5, 5, 5, 5, 5, 5, 5, 5];
 impl From<(String, u8)> for Person {
     // Convert a String to a Person
     fn from((name, age): (String, u8)) -> Self {
-        Person {
-            name,
-            age,
-            phone: DEFAULT_PHONE,
-            favorite_color: "Unknown".to_string(),
-        }
+        Person { name, age, phone: DEFAULT_PHONE, favorite_color: None }
+    }
+}
+
+// And the other direction. This drops the phone and color,
+// so it only round-trips for people built with From above.
+impl From<Person> for (String, u8) {
+    fn from(person: Person) -> Self {
+        (person.name, person.age)
+    }
+}
+
+impl Person {
+    // as_deref turns &Option<String> into Option<&str>
+    pub fn favorite_color(&self) -> Option<&str> {
+        self.favorite_color.as_deref()
     }
 }
 
@@ -227,6 +484,416 @@ fn test_from_into() {
     // into is a method, so called with .into().
 }
 
+#[test]
+fn test_person_into_tuple() {
+    let caleb = Person::from(("caleb".to_owned(), 26));
+    let (name, age): (String, u8) = caleb.into();
+    assert_eq!(name, "caleb");
+    assert_eq!(age, 26);
+}
+
+// FNV-1a: a tiny hash that, unlike the std HashMap hasher, is guaranteed
+// to give the same output on every run and every Rust version.
+fn stable_hash(s: &str) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in s.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+impl Person {
+    // Strip personally identifying info, keeping age and color
+    // around for analytics. Same person in => same pseudonym out.
+    pub fn anonymize(&self) -> Person {
+        Person {
+            name: format!("anon-{:016x}", stable_hash(&self.name)),
+            phone: DEFAULT_PHONE,
+            ..self.clone()
+        }
+    }
+}
+
+#[test]
+fn test_anonymize() {
+    let caleb = Person {
+        name: "caleb".to_owned(),
+        age: 26,
+        phone: [2, 1, 5, 8, 9, 8, 1, 2, 3, 4],
+        favorite_color: Some("Purple".to_owned()),
+    };
+    let anon1 = caleb.anonymize();
+    let anon2 = caleb.anonymize();
+    assert_eq!(anon1.name, anon2.name);
+    assert_ne!(anon1.name, caleb.name);
+    assert_eq!(anon1.phone, DEFAULT_PHONE);
+    assert_eq!(anon1.age, caleb.age);
+    assert_eq!(anon1.favorite_color, caleb.favorite_color);
+}
+
+impl AddressBook {
+    // A copy of the book with everyone anonymized.
+    // Two different names could (very rarely) hash to the same pseudonym,
+    // so on a collision we append a counter.
+    pub fn anonymized(&self) -> AddressBook {
+        let mut book = AddressBook::new();
+        for person in self.by_name.values() {
+            let mut anon = person.anonymize();
+            let base = anon.name.clone();
+            let mut counter = 2;
+            while book.by_name.contains_key(&anon.name) {
+                anon.name = format!("{}-{}", base, counter);
+                counter += 1;
+            }
+            book.add_person(anon);
+        }
+        book
+    }
+}
+
+#[test]
+fn test_anonymized_book() {
+    let mut book = AddressBook::new();
+    book.add_person(Person::from(("caleb".to_owned(), 26)));
+    book.add_person(Person::from(("alice".to_owned(), 26)));
+    book.add_person(Person::from(("bob".to_owned(), 40)));
+    assert_eq!(book.age_histogram()[&26], 2);
+    let anon = book.anonymized();
+    assert_eq!(anon.len(), book.len());
+    assert_eq!(anon.age_histogram(), book.age_histogram());
+    assert!(!anon.by_name.contains_key("caleb"));
+}
+
+impl Person {
+    // age is a u8, so 255 + 1 would overflow. checked_add returns None
+    // in that case instead of panicking (debug) or wrapping (release).
+    pub fn have_birthday(&mut self) -> Result<(), String> {
+        self.age = self
+            .age
+            .checked_add(1)
+            .ok_or_else(|| format!("{} is already {}", self.name, self.age))?;
+        Ok(())
+    }
+}
+
+impl AddressBook {
+    // Birthday for someone in the book. Going through add_person
+    // moves them to the right by_age bucket.
+    pub fn age_up(&mut self, name: &str) -> Result<(), String> {
+        let mut person = match self.by_name.get(name) {
+            Some(person) => person.clone(),
+            None => return Err(format!("No person named {}", name)),
+        };
+        person.have_birthday()?;
+        self.add_person(person);
+        Ok(())
+    }
+
+    // Change someone's age in place, moving them between by_age buckets
+    pub fn set_age(&mut self, name: &str, new_age: u8) -> Result<(), String> {
+        let mut person = match self.by_name.get(name) {
+            Some(person) => person.clone(),
+            None => return Err(format!("No person named {}", name)),
+        };
+        person.age = new_age;
+        self.add_person(person);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_age_up() {
+    let mut book = AddressBook::new();
+    book.add_person(Person::from(("caleb".to_owned(), 26)));
+    book.age_up("caleb").unwrap();
+    assert_eq!(book["caleb"].age, 27);
+    assert!(!book.by_age.contains_key(&26));
+    assert_eq!(book.by_age[&27].len(), 1);
+    assert!(book.age_up("nobody").is_err());
+}
+
+#[test]
+fn test_set_age() {
+    let mut book = AddressBook::new();
+    book.add_person(Person::from(("caleb".to_owned(), 26)));
+    book.add_person(Person::from(("alice".to_owned(), 26)));
+    book.set_age("caleb", 40).unwrap();
+    assert_eq!(book["caleb"].age, 40);
+    assert_eq!(book.by_age[&26].len(), 1);
+    assert_eq!(book.by_age[&26][0].name, "alice");
+    assert_eq!(book.by_age[&40][0].name, "caleb");
+    assert!(book.set_age("nobody", 1).is_err());
+}
+
+impl AddressBook {
+    // Mutate everyone in the book at once. f might change anyone's age
+    // (or even name), so we rebuild the indexes afterward instead of
+    // handing out &mut Person into them.
+    // If f gives two people the same name, one would silently replace
+    // the other, so that's an error and the book is left unchanged.
+    pub fn update_all<F: FnMut(&mut Person)>(
+        &mut self,
+        mut f: F,
+    ) -> Result<(), String> {
+        let mut updated = Vec::with_capacity(self.len());
+        let mut names = HashSet::new();
+        for person in self.by_name.values() {
+            let mut person = person.clone();
+            f(&mut person);
+            if !names.insert(person.name.clone()) {
+                return Err(format!(
+                    "two people would be named {}",
+                    person.name
+                ));
+            }
+            updated.push(person);
+        }
+        *self = updated.into_iter().collect();
+        Ok(())
+    }
+}
+
+#[test]
+fn test_update_all() {
+    let mut book = AddressBook::new();
+    book.add_person(Person::from(("caleb".to_owned(), 26)));
+    book.add_person(Person::from(("alice".to_owned(), 26)));
+    book.add_person(Person::from(("bob".to_owned(), 40)));
+    book.update_all(|person| person.age += 1).unwrap();
+    assert_eq!(book["caleb"].age, 27);
+    let expected: BTreeMap<u8, usize> =
+        vec![(27, 2), (41, 1)].into_iter().collect();
+    assert_eq!(book.age_histogram(), expected);
+}
+
+#[test]
+fn test_update_all_name_clash() {
+    let mut book = AddressBook::new();
+    book.add_person(Person::from(("a".to_owned(), 26)));
+    book.add_person(Person::from(("b".to_owned(), 30)));
+    assert!(book.update_all(|person| person.name = "x".to_owned()).is_err());
+    // Nothing changed
+    assert_eq!(book.len(), 2);
+    assert!(book.contains_name("a") && book.contains_name("b"));
+    // Renaming to distinct names is fine
+    book.update_all(|person| person.name.push('!')).unwrap();
+    assert!(book.contains_name("a!") && book.contains_name("b!"));
+    assert!(book.is_consistent());
+}
+
+#[test]
+fn test_have_birthday_overflow() {
+    let mut old = Person::from(("methuselah".to_owned(), 254));
+    assert!(old.have_birthday().is_ok());
+    assert_eq!(old.age, 255);
+    assert!(old.have_birthday().is_err());
+    assert_eq!(old.age, 255);
+}
+
+impl AddressBook {
+    // Take out one of the oldest people (the first by name, if there's
+    // a tie). remove_person keeps all the indexes in sync.
+    pub fn remove_oldest(&mut self) -> Option<Person> {
+        let oldest_age = *self.by_age.keys().next_back()?;
+        let name =
+            self.by_age[&oldest_age].iter().map(|p| p.name.clone()).min()?;
+        self.remove_person(&name)
+    }
+}
+
+#[test]
+fn test_remove_oldest() {
+    let mut book = AddressBook::new();
+    book.add_person(Person::from(("caleb".to_owned(), 26)));
+    book.add_person(Person::from(("alice".to_owned(), 70)));
+    book.add_person(Person::from(("bob".to_owned(), 40)));
+    assert_eq!(book.remove_oldest().unwrap().name, "alice");
+    assert_eq!(book.remove_oldest().unwrap().name, "bob");
+    assert_eq!(book.len(), 1);
+    assert_eq!(book.age_histogram().keys().collect::<Vec<_>>(), vec![&26]);
+}
+
+impl AddressBook {
+    // Someone whose age is nearest to target. On a tie, the younger
+    // age wins, and within an age the first name alphabetically.
+    pub fn closest_to_age(&self, target: u8) -> Option<&Person> {
+        let age = self.by_age.keys().min_by_key(|&&age| {
+            ((i16::from(age) - i16::from(target)).abs(), age)
+        })?;
+        self.by_age[age].iter().min_by(|a, b| a.name.cmp(&b.name))
+    }
+}
+
+#[test]
+fn test_closest_to_age() {
+    let mut book = AddressBook::new();
+    assert!(book.closest_to_age(27).is_none());
+    book.add_person(Person::from(("caleb".to_owned(), 26)));
+    book.add_person(Person::from(("bob".to_owned(), 30)));
+    assert_eq!(book.closest_to_age(27).unwrap().name, "caleb");
+    assert_eq!(book.closest_to_age(29).unwrap().name, "bob");
+    // 28 is 2 away from both: the younger one wins
+    assert_eq!(book.closest_to_age(28).unwrap().name, "caleb");
+}
+
+impl AddressBook {
+    // The most common age. by_age is ordered, and max_by_key returns the
+    // *last* maximum, so we walk it oldest first to let the younger age
+    // win ties.
+    pub fn age_mode(&self) -> Option<u8> {
+        self.by_age
+            .iter()
+            .rev()
+            .max_by_key(|(_, people)| people.len())
+            .map(|(&age, _)| age)
+    }
+}
+
+#[test]
+fn test_age_mode() {
+    let mut book = AddressBook::new();
+    assert_eq!(book.age_mode(), None);
+    book.add_person(Person::from(("caleb".to_owned(), 26)));
+    book.add_person(Person::from(("alice".to_owned(), 26)));
+    book.add_person(Person::from(("bob".to_owned(), 30)));
+    assert_eq!(book.age_mode(), Some(26));
+    // Two at 26 and two at 30: the younger age wins
+    book.add_person(Person::from(("dave".to_owned(), 30)));
+    assert_eq!(book.age_mode(), Some(26));
+}
+
+impl Person {
+    // "child" under 18, "adult" from 18 to 64, "senior" from 65 on
+    pub fn age_group(&self) -> &'static str {
+        match self.age {
+            0..=17 => "child",
+            18..=64 => "adult",
+            _ => "senior",
+        }
+    }
+}
+
+impl AddressBook {
+    // How many people fall in each age_group(). Groups with nobody in
+    // them are left out.
+    pub fn count_by_age_group(&self) -> BTreeMap<&'static str, usize> {
+        let mut counts = BTreeMap::new();
+        for person in self.by_name.values() {
+            *counts.entry(person.age_group()).or_insert(0) += 1;
+        }
+        counts
+    }
+}
+
+#[test]
+fn test_age_group_boundaries() {
+    let group = |age| Person::from(("caleb".to_owned(), age)).age_group();
+    assert_eq!(group(17), "child");
+    assert_eq!(group(18), "adult");
+    assert_eq!(group(64), "adult");
+    assert_eq!(group(65), "senior");
+}
+
+#[test]
+fn test_count_by_age_group() {
+    let mut book = AddressBook::new();
+    for &(name, age) in
+        &[("caleb", 17), ("alice", 18), ("bob", 64), ("dave", 65), ("erin", 18)]
+    {
+        book.add_person(Person::from((name.to_owned(), age)));
+    }
+    let counts = book.count_by_age_group();
+    assert_eq!(counts["child"], 1);
+    assert_eq!(counts["adult"], 3);
+    assert_eq!(counts["senior"], 1);
+}
+
+impl AddressBook {
+    // For each age in [low, high] that has anyone, the age and how many.
+    // by_age is ordered, so this is a range query: ages outside the
+    // range are never visited.
+    pub fn ages_between(&self, low: u8, high: u8) -> Vec<(u8, usize)> {
+        if low > high {
+            return Vec::new();
+        }
+        self.by_age
+            .range(low..=high)
+            .map(|(&age, people)| (age, people.len()))
+            .collect()
+    }
+}
+
+#[test]
+fn test_ages_between() {
+    let mut book = AddressBook::new();
+    for &(name, age) in
+        &[("caleb", 26), ("alice", 26), ("bob", 30), ("dave", 50), ("erin", 18)]
+    {
+        book.add_person(Person::from((name.to_owned(), age)));
+    }
+    assert_eq!(book.ages_between(20, 40), vec![(26, 2), (30, 1)]);
+    assert_eq!(book.ages_between(18, 18), vec![(18, 1)]);
+    assert!(book.ages_between(31, 49).is_empty());
+    assert!(book.ages_between(40, 20).is_empty());
+}
+
+impl AddressBook {
+    // The percentage of everyone who is strictly younger than name.
+    // The youngest person is always at 0. by_age is ordered, so the
+    // younger people are just the buckets before their age.
+    pub fn age_percentile(&self, name: &str) -> Option<f64> {
+        let age = self.by_name.get(name)?.age;
+        let younger: usize =
+            self.by_age.range(..age).map(|(_, p)| p.len()).sum();
+        Some(younger as f64 / self.len() as f64 * 100.0)
+    }
+}
+
+#[test]
+fn test_age_percentile() {
+    let mut book = AddressBook::new();
+    for &(name, age) in
+        &[("erin", 18), ("caleb", 26), ("alice", 30), ("bob", 40), ("dave", 50)]
+    {
+        book.add_person(Person::from((name.to_owned(), age)));
+    }
+    // alice is the median: 2 of the 5 people are younger
+    assert_eq!(book.age_percentile("alice"), Some(40.0));
+    assert_eq!(book.age_percentile("erin"), Some(0.0));
+    assert_eq!(book.age_percentile("dave"), Some(80.0));
+    // Someone the same age doesn't count as younger
+    book.add_person(Person::from(("frank".to_owned(), 30)));
+    assert_eq!(book.age_percentile("alice"), book.age_percentile("frank"));
+    assert_eq!(book.age_percentile("nobody"), None);
+}
+
+impl AddressBook {
+    // Up to n different people, chosen at random.
+    // Only available with `--features rand`.
+    #[cfg(feature = "rand")]
+    pub fn sample(&self, n: usize) -> Vec<&Person> {
+        use rand::seq::IteratorRandom;
+        if n >= self.len() {
+            return self.by_name.values().collect();
+        }
+        self.by_name.values().choose_multiple(&mut rand::thread_rng(), n)
+    }
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn test_sample() {
+    let mut book = AddressBook::new();
+    book.add_person(Person::from(("caleb".to_owned(), 26)));
+    book.add_person(Person::from(("alice".to_owned(), 30)));
+    book.add_person(Person::from(("bob".to_owned(), 40)));
+    let sample = book.sample(2);
+    assert_eq!(sample.len(), 2);
+    assert_ne!(sample[0].name, sample[1].name);
+    assert_eq!(book.sample(10).len(), 3);
+}
+
 /*
     Default
 
@@ -247,8 +914,13 @@ fn test_from_into() {
 
 impl Default for AddressBook {
     fn default() -> Self {
-        Self { by_name: Default::default(), by_age: Default::default() }
-        // ^^ HashMap implements Default!
+        Self {
+            by_name: Default::default(),
+            by_age: Default::default(),
+            by_phone: Default::default(),
+            suffixed: Default::default(),
+        }
+        // ^^ BTreeMap and HashMap both implement Default!
     }
 }
 
@@ -365,6 +1037,8 @@ impl Index<&str> for AddressBook {
 // change what a PhoneNumber is and how it's implemented,
 // so you decided to abstract it as a type.
 
+// Deriving Hash and Eq lets PhoneNumber be a HashMap/HashSet key
+#[derive(PartialEq, Eq, Hash)]
 pub struct PhoneNumber([u8; 10]);
 
 pub struct Person2 {
@@ -424,57 +1098,1303 @@ impl Person2 {
     }
 }
 
-/*
-    A couple more traits before we move on to iplementing our own.
+// Since PhoneNumber is our own type, we can also give it a nicer
+// Display than the raw array of digits, e.g. (555) 555-5555
+// write! would ignore width and alignment ("{:>16}"), so we build the
+// string first and let f.pad apply them.
+impl Display for PhoneNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let d = &self.0;
+        let s = format!(
+            "({}{}{}) {}{}{}-{}{}{}{}",
+            d[0], d[1], d[2], d[3], d[4], d[5], d[6], d[7], d[8], d[9],
+        );
+        f.pad(&s)
+    }
+}
 
-    - Read / Write
+#[test]
+fn test_display_phone_number_padding() {
+    let phone = PhoneNumber([2, 1, 5, 8, 9, 8, 1, 2, 3, 4]);
+    assert_eq!(format!("{:>16}", phone), "  (215) 898-1234");
+    assert_eq!(format!("{:<16}|", phone), "(215) 898-1234  |");
+    assert_eq!(format!("{:^16}", phone), " (215) 898-1234 ");
+    // Too narrow a width doesn't truncate
+    assert_eq!(format!("{:4}", phone), "(215) 898-1234");
+}
 
-      For objects that can be read or written to.
+// Same as Display for Person, but the phone gets printed too
+// (using the PhoneNumber Display just above).
+impl Display for Person2 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Person with name {}, age {}, phone {}",
+            self.name, self.age, self.phone,
+        )
+    }
+}
 
-    println!("{}", ...)
-    write!(f, "{}", ...)
-    ^ for f implementing the Write trait.
+#[test]
+fn test_display_person2() {
+    let caleb = Person2 {
+        name: "caleb".to_owned(),
+        age: 26,
+        phone: PhoneNumber([2, 1, 5, 8, 9, 8, 1, 2, 3, 4]),
+        favorite_color: "Purple".to_owned(),
+    };
+    assert_eq!(
+        caleb.to_string(),
+        "Person with name caleb, age 26, phone (215) 898-1234"
+    );
+}
 
-    Q: Why is this useful?
+impl Person {
+    // Person stores a raw [u8; 10], but wrapping it in a PhoneNumber
+    // (a free, zero-cost wrap) lets us reuse its Display.
+    pub fn display_phone(&self) -> String {
+        PhoneNumber(self.phone).to_string()
+    }
+}
 
-    A: Very different objects are writable.
-    - a terminal interface
-    - standard output
-    - a network connection
-    - a internal buffer
-    - A file
+#[test]
+fn test_display_phone() {
+    let caleb = Person {
+        name: "caleb".to_owned(),
+        age: 26,
+        phone: [2, 1, 5, 8, 9, 8, 1, 2, 3, 4],
+        favorite_color: None,
+    };
+    assert_eq!(caleb.display_phone(), "(215) 898-1234");
+}
 
-    Recall: Traits define shared behavior across different types!
+impl Person {
+    // "caleb smith" -> "CS". split_whitespace skips any extra spaces,
+    // and an empty name just gives an empty string.
+    pub fn initials(&self) -> String {
+        self.name
+            .split_whitespace()
+            .filter_map(|word| word.chars().next())
+            .flat_map(char::to_uppercase)
+            .collect()
+    }
+}
 
-    Similarly Read is for stdin-like buffers, input files, etc.
-*/
+#[test]
+fn test_initials_single_word() {
+    assert_eq!(Person::from(("caleb".to_owned(), 26)).initials(), "C");
+    assert_eq!(Person::from(("".to_owned(), 26)).initials(), "");
+}
 
-/*
-    Iterator
-*/
+#[test]
+fn test_initials_multi_word() {
+    assert_eq!(Person::from(("caleb smith".to_owned(), 26)).initials(), "CS");
+    let spaced = Person::from(("  ada   king lovelace ".to_owned(), 36));
+    assert_eq!(spaced.initials(), "AKL");
+}
 
-pub fn iterator_example() {
-    for x in 0..10 {
-        println!("{}", x);
+#[test]
+fn test_hash_phone_number() {
+    let mut phones = HashSet::new();
+    phones.insert(PhoneNumber([2, 1, 5, 8, 9, 8, 1, 2, 3, 4]));
+    phones.insert(PhoneNumber([2, 1, 5, 8, 9, 8, 1, 2, 3, 4]));
+    assert_eq!(phones.len(), 1);
+}
+
+impl PhoneNumber {
+    // Display form with all but the last four digits hidden,
+    // e.g. (***) ***-5555
+    pub fn mask(&self) -> String {
+        let mut digits_seen = 0;
+        self.to_string()
+            .chars()
+            .map(|c| {
+                if c.is_ascii_digit() {
+                    digits_seen += 1;
+                    if digits_seen <= 6 {
+                        return '*';
+                    }
+                }
+                c
+            })
+            .collect()
     }
-    // Internally: for x in I means that I is a type implementing
-    // the Iterator trait.
+}
 
-    // Most collections offer a way of getting an iterator from
-    // the object; where "iterator" means something that implements
-    // the Iterator trait.
+#[test]
+fn test_mask_phone_number() {
+    let phone = PhoneNumber([1, 2, 3, 4, 5, 6, 7, 8, 9, 0]);
+    assert_eq!(phone.mask(), "(***) ***-7890");
+}
 
-    let v = vec![1, 2, 3];
-    for x in v.iter() {
-        println!("{}", x);
+impl PhoneNumber {
+    // North American area codes never start with 0 or 1
+    pub fn is_valid_area_code(&self) -> bool {
+        self.0[0] >= 2
     }
 
-    let h: HashMap<usize, usize> = HashMap::new();
-    for x in h.iter() {
+    pub fn digit_sum(&self) -> u32 {
+        self.0.iter().map(|&d| u32::from(d)).sum()
+    }
+
+    pub fn last_four(&self) -> [u8; 4] {
+        [self.0[6], self.0[7], self.0[8], self.0[9]]
+    }
+
+    // A random (valid) number for test data.
+    // Only available with `--features rand`.
+    #[cfg(feature = "rand")]
+    pub fn random() -> PhoneNumber {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+        let mut digits = [0; 10];
+        digits[0] = rng.gen_range(2..10);
+        for digit in digits.iter_mut().skip(1) {
+            *digit = rng.gen_range(0..10);
+        }
+        PhoneNumber(digits)
+    }
+}
+
+#[test]
+fn test_is_valid_area_code() {
+    assert!(PhoneNumber([2, 1, 5, 8, 9, 8, 1, 2, 3, 4]).is_valid_area_code());
+    assert!(!PhoneNumber([1, 2, 3, 4, 5, 6, 7, 8, 9, 0]).is_valid_area_code());
+}
+
+#[test]
+fn test_digit_sum_and_last_four() {
+    let phone = PhoneNumber([2, 1, 5, 8, 9, 8, 1, 2, 3, 4]);
+    assert_eq!(phone.digit_sum(), 43);
+    assert_eq!(phone.last_four(), [1, 2, 3, 4]);
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn test_random_phone_number() {
+    for _ in 0..100 {
+        let phone = PhoneNumber::random();
+        assert!(phone.is_valid_area_code());
+        assert!(phone.iter().all(|&d| d <= 9));
+    }
+}
+
+// Parsing a phone number: ignore the usual formatting characters,
+// and require exactly 10 digits to be left.
+impl FromStr for PhoneNumber {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut digits = [0; 10];
+        let mut count = 0;
+        for c in s.chars() {
+            match c {
+                '0'..='9' => {
+                    if count == 10 {
+                        return Err(format!("too many digits in {:?}", s));
+                    }
+                    digits[count] = c as u8 - b'0';
+                    count += 1;
+                }
+                ' ' | '(' | ')' | '-' | '.' => {}
+                _ => return Err(format!("unexpected {:?} in {:?}", c, s)),
+            }
+        }
+        if count != 10 {
+            return Err(format!("expected 10 digits in {:?}", s));
+        }
+        Ok(PhoneNumber(digits))
+    }
+}
+
+// Do two strings name the same phone number, however they're formatted?
+// Anything that doesn't parse is not the same as anything.
+pub fn same_number(a: &str, b: &str) -> bool {
+    match (a.parse::<PhoneNumber>(), b.parse::<PhoneNumber>()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+#[test]
+fn test_same_number() {
+    assert!(same_number("(555) 555-5555", "5555555555"));
+    assert!(same_number("215.898.1234", "(215) 898-1234"));
+    assert!(!same_number("(215) 898-1234", "(215) 898-1235"));
+}
+
+#[test]
+fn test_same_number_parse_failure() {
+    assert!(!same_number("555-5555", "555-5555"));
+    assert!(!same_number("(555) 555-555x", "5555555555"));
+    assert!("555 555 55555".parse::<PhoneNumber>().is_err());
+}
+
+impl Person {
+    // Phone numbers come to us as text far more often than as digit
+    // arrays, so let PhoneNumber's FromStr do the parsing.
+    pub fn with_phone_str(
+        name: String,
+        age: u8,
+        phone: &str,
+        color: String,
+    ) -> Result<Person, String> {
+        let PhoneNumber(phone) = phone.parse()?;
+        Ok(Person { name, age, phone, favorite_color: Some(color) })
+    }
+}
+
+#[test]
+fn test_with_phone_str() {
+    let caleb = Person::with_phone_str(
+        "caleb".to_owned(),
+        26,
+        "(555) 123-4567",
+        "Purple".to_owned(),
+    )
+    .unwrap();
+    assert_eq!(caleb.phone, [5, 5, 5, 1, 2, 3, 4, 5, 6, 7]);
+    assert_eq!(caleb.favorite_color(), Some("Purple"));
+    assert!(Person::with_phone_str(
+        "caleb".to_owned(),
+        26,
+        "555-1234",
+        "Purple".to_owned()
+    )
+    .is_err());
+}
+
+// Person still stores a raw [u8; 10], but we can wrap it temporarily
+// to reuse the Display above.
+impl AddressBook {
+    // Map each name to their formatted phone number.
+    // People who still have the placeholder DEFAULT_PHONE are skipped.
+    pub fn phone_directory(&self) -> BTreeMap<String, String> {
+        self.directory_with(|phone| phone.to_string())
+    }
+    // Same, but with the phone numbers masked for safe sharing
+    pub fn masked_directory(&self) -> BTreeMap<String, String> {
+        self.directory_with(PhoneNumber::mask)
+    }
+    fn directory_with<F: Fn(&PhoneNumber) -> String>(
+        &self,
+        format_phone: F,
+    ) -> BTreeMap<String, String> {
+        self.by_name
+            .values()
+            .filter(|person| person.phone != DEFAULT_PHONE)
+            .map(|person| {
+                (person.name.clone(), format_phone(&PhoneNumber(person.phone)))
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_phone_directory() {
+    let mut book = AddressBook::new();
+    book.add_person(Person {
+        name: "caleb".to_owned(),
+        age: 26,
+        phone: [2, 1, 5, 8, 9, 8, 1, 2, 3, 4],
+        favorite_color: Some("Purple".to_owned()),
+    });
+    book.add_person(Person::from(("caleb2".to_owned(), 26)));
+    let directory = book.phone_directory();
+    assert_eq!(directory.len(), 1);
+    assert_eq!(directory["caleb"], "(215) 898-1234");
+    assert!(!directory.contains_key("caleb2"));
+}
+
+#[test]
+fn test_masked_directory() {
+    let mut book = AddressBook::new();
+    book.add_person(Person {
+        name: "caleb".to_owned(),
+        age: 26,
+        phone: [2, 1, 5, 8, 9, 8, 1, 2, 3, 4],
+        favorite_color: Some("Purple".to_owned()),
+    });
+    book.add_person(Person::from(("caleb2".to_owned(), 26)));
+    let directory = book.masked_directory();
+    assert_eq!(directory.len(), 1);
+    assert_eq!(directory["caleb"], "(***) ***-1234");
+}
+
+impl AddressBook {
+    // Everyone whose phone starts with the given area code, by name
+    pub fn people_in_area_code(&self, area: [u8; 3]) -> Vec<&Person> {
+        self.by_name
+            .values()
+            .filter(|p| p.phone != DEFAULT_PHONE && p.phone[0..3] == area)
+            .collect()
+    }
+}
+
+#[test]
+fn test_people_in_area_code() {
+    let mut book = AddressBook::new();
+    for &(name, phone) in &[
+        ("caleb", [2, 1, 5, 8, 9, 8, 1, 2, 3, 4]),
+        ("alice", [2, 1, 5, 5, 5, 5, 0, 0, 0, 0]),
+        ("bob", [6, 1, 0, 5, 5, 5, 0, 0, 0, 0]),
+    ] {
+        book.add_person(Person {
+            name: name.to_owned(),
+            age: 26,
+            phone,
+            favorite_color: None,
+        });
+    }
+    let names: Vec<&str> = book
+        .people_in_area_code([2, 1, 5])
+        .iter()
+        .map(|p| p.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["alice", "caleb"]);
+    book.add_person(Person::from(("dave".to_owned(), 26)));
+    assert!(book.people_in_area_code([5, 5, 5]).is_empty());
+}
+
+impl AddressBook {
+    // Every real phone number shared by more than one person, with the
+    // names that share it. by_phone already groups them (and never has
+    // DEFAULT_PHONE); we just sort, by phone and then by name.
+    pub fn phone_collisions(&self) -> Vec<(Vec<String>, [u8; 10])> {
+        let mut collisions: Vec<(Vec<String>, [u8; 10])> = self
+            .by_phone
+            .iter()
+            .filter(|(_, names)| names.len() > 1)
+            .map(|(&phone, names)| {
+                let mut names = names.clone();
+                names.sort();
+                (names, phone)
+            })
+            .collect();
+        collisions.sort_by_key(|&(_, phone)| phone);
+        collisions
+    }
+}
+
+#[test]
+fn test_phone_collisions() {
+    let shared = [2, 1, 5, 5, 5, 5, 1, 2, 3, 4];
+    let mut book = AddressBook::new();
+    for &(name, phone) in &[
+        ("caleb", shared),
+        ("alice", shared),
+        ("bob", [6, 1, 0, 5, 5, 5, 0, 0, 0, 0]),
+    ] {
+        book.add_person(Person {
+            name: name.to_owned(),
+            age: 26,
+            phone,
+            favorite_color: None,
+        });
+    }
+    // dave and erin both have DEFAULT_PHONE, which doesn't count
+    book.add_person(Person::from(("dave".to_owned(), 26)));
+    book.add_person(Person::from(("erin".to_owned(), 30)));
+    assert_eq!(
+        book.phone_collisions(),
+        vec![(vec!["alice".to_owned(), "caleb".to_owned()], shared)]
+    );
+}
+
+impl AddressBook {
+    // People grouped by favorite color. The BTreeMap keeps the colors
+    // sorted, and since by_name is visited in name order, so is each group.
+    // People with no known favorite color are left out.
+    pub fn group_by_color(&self) -> BTreeMap<String, Vec<&Person>> {
+        let mut groups: BTreeMap<String, Vec<&Person>> = BTreeMap::new();
+        for person in self.by_name.values() {
+            if let Some(ref color) = person.favorite_color {
+                groups.entry(color.clone()).or_default().push(person);
+            }
+        }
+        groups
+    }
+}
+
+#[test]
+fn test_group_by_color() {
+    let mut book = AddressBook::new();
+    for &(name, color) in
+        &[("caleb", "Purple"), ("alice", "Green"), ("bob", "Purple")]
+    {
+        book.add_person(Person {
+            name: name.to_owned(),
+            age: 26,
+            phone: DEFAULT_PHONE,
+            favorite_color: Some(color.to_owned()),
+        });
+    }
+    book.add_person(Person::from(("dave".to_owned(), 26)));
+    let groups = book.group_by_color();
+    let names = |color: &str| -> Vec<&str> {
+        groups[color].iter().map(|p| p.name.as_str()).collect()
+    };
+    assert_eq!(groups.keys().collect::<Vec<_>>(), vec!["Green", "Purple"]);
+    assert_eq!(names("Green"), vec!["alice"]);
+    assert_eq!(names("Purple"), vec!["bob", "caleb"]);
+}
+
+// A search: every field that is Some must match, None means "any".
+// Default gives the query that matches everyone.
+#[derive(Clone, Debug, Default)]
+pub struct PersonQuery {
+    pub name: Option<String>, // matches any name containing this
+    pub min_age: Option<u8>,
+    pub max_age: Option<u8>,
+    pub color: Option<String>, // people with no known color never match
+}
+
+impl Person {
+    pub fn matches(&self, q: &PersonQuery) -> bool {
+        q.name.as_ref().is_none_or(|name| self.name.contains(name.as_str()))
+            && q.min_age.is_none_or(|min| self.age >= min)
+            && q.max_age.is_none_or(|max| self.age <= max)
+            && q.color
+                .as_ref()
+                .is_none_or(|color| self.favorite_color.as_ref() == Some(color))
+    }
+}
+
+impl AddressBook {
+    // Everyone matching q, in name order
+    pub fn query(&self, q: &PersonQuery) -> Vec<&Person> {
+        self.by_name.values().filter(|p| p.matches(q)).collect()
+    }
+}
+
+#[test]
+fn test_query() {
+    let mut book = AddressBook::new();
+    for &(name, age, color) in &[
+        ("caleb", 26, "Purple"),
+        ("alice", 30, "Green"),
+        ("bob", 40, "Purple"),
+        ("dave", 28, "Purple"),
+    ] {
+        book.add_person(Person {
+            name: name.to_owned(),
+            age,
+            phone: DEFAULT_PHONE,
+            favorite_color: Some(color.to_owned()),
+        });
+    }
+    book.add_person(Person::from(("erin".to_owned(), 27)));
+    let names = |q: &PersonQuery| -> Vec<String> {
+        book.query(q).iter().map(|p| p.name.clone()).collect()
+    };
+    let q = PersonQuery {
+        min_age: Some(25),
+        max_age: Some(35),
+        color: Some("Purple".to_owned()),
+        ..Default::default()
+    };
+    assert_eq!(names(&q), vec!["caleb", "dave"]);
+    let q = PersonQuery { name: Some("a".to_owned()), ..q };
+    assert_eq!(names(&q), vec!["caleb", "dave"]);
+    // An empty query matches everyone
+    assert_eq!(book.query(&PersonQuery::default()).len(), 5);
+}
+
+impl AddressBook {
+    // Everyone, cloned out of the book, in name order
+    pub fn to_sorted_vec(&self) -> Vec<Person> {
+        self.by_name.values().cloned().collect()
+    }
+}
+
+#[test]
+fn test_to_sorted_vec() {
+    let book: AddressBook = vec![
+        Person::from(("caleb".to_owned(), 26)),
+        Person::from(("alice".to_owned(), 30)),
+        Person::from(("bob".to_owned(), 40)),
+    ]
+    .into_iter()
+    .collect();
+    let names: Vec<String> =
+        book.to_sorted_vec().into_iter().map(|p| p.name).collect();
+    assert_eq!(names, vec!["alice", "bob", "caleb"]);
+}
+
+impl AddressBook {
+    // All names starting with prefix, sorted (e.g. for autocomplete).
+    // Names with the prefix are all next to each other in the BTreeMap:
+    // jump to the first one, and stop at the first one that doesn't match.
+    pub fn names_with_prefix(&self, prefix: &str) -> Vec<&str> {
+        self.by_name
+            .range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+            .map(|(name, _)| name.as_str())
+            .take_while(|name| name.starts_with(prefix))
+            .collect()
+    }
+}
+
+#[test]
+fn test_names_with_prefix() {
+    let book: AddressBook = vec!["alice", "alan", "bob"]
+        .into_iter()
+        .map(|name| Person::from((name.to_owned(), 30)))
+        .collect();
+    assert_eq!(book.names_with_prefix("al"), vec!["alan", "alice"]);
+    assert!(book.names_with_prefix("z").is_empty());
+}
+
+#[test]
+fn test_by_name_btreemap() {
+    let book: AddressBook = vec!["bob", "alan", "alice", "al", "alb", "caleb"]
+        .into_iter()
+        .map(|name| Person::from((name.to_owned(), 30)))
+        .collect();
+    assert_eq!(
+        book.names_with_prefix("al"),
+        vec!["al", "alan", "alb", "alice"]
+    );
+    assert_eq!(
+        book.names_with_prefix(""),
+        vec!["al", "alan", "alb", "alice", "bob", "caleb"]
+    );
+    assert!(book.names_with_prefix("alz").is_empty());
+    // Lookups by name still work
+    assert_eq!(book["caleb"].age, 30);
+    assert!(book.contains_name("alb"));
+    assert!(!book.contains_name("a"));
+}
+
+// Quote a string for JSON, escaping the characters JSON requires.
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                out.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+impl AddressBook {
+    // Hand-written JSON export, so no serde dependency is needed.
+    // by_name is ordered, so the output is deterministic.
+    pub fn to_json(&self) -> String {
+        let entries: Vec<String> = self
+            .by_name
+            .values()
+            .map(|person| {
+                let phone: Vec<String> =
+                    person.phone.iter().map(|d| d.to_string()).collect();
+                format!(
+                    "{{\"name\":{},\"age\":{},\"phone\":[{}],\"favorite_color\":{}}}",
+                    json_string(&person.name),
+                    person.age,
+                    phone.join(","),
+                    person
+                        .favorite_color
+                        .as_ref()
+                        .map_or("null".to_string(), |c| json_string(c)),
+                )
+            })
+            .collect();
+        format!("[{}]", entries.join(","))
+    }
+}
+
+#[test]
+fn test_to_json() {
+    let mut book = AddressBook::new();
+    assert_eq!(book.to_json(), "[]");
+    book.add_person(Person {
+        name: "caleb \"the TA\"".to_owned(),
+        age: 26,
+        phone: [2, 1, 5, 8, 9, 8, 1, 2, 3, 4],
+        favorite_color: Some("Purple".to_owned()),
+    });
+    book.add_person(Person::from(("alice".to_owned(), 30)));
+    assert_eq!(
+        book.to_json(),
+        "[{\"name\":\"alice\",\"age\":30,\"phone\":[5,5,5,5,5,5,5,5,5,5],\
+         \"favorite_color\":null},\
+         {\"name\":\"caleb \\\"the TA\\\"\",\"age\":26,\
+         \"phone\":[2,1,5,8,9,8,1,2,3,4],\"favorite_color\":\"Purple\"}]"
+    );
+}
+
+// With the serde feature, Person derives Deserialize, so we can
+// read back what to_json writes.
+#[cfg(feature = "serde")]
+impl AddressBook {
+    pub fn from_json(s: &str) -> Result<AddressBook, serde_json::Error> {
+        let people: Vec<Person> = serde_json::from_str(s)?;
+        Ok(people.into_iter().collect())
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_json_round_trip() {
+    let mut book = AddressBook::new();
+    book.add_person(Person {
+        name: "caleb \"the TA\"".to_owned(),
+        age: 26,
+        phone: [2, 1, 5, 8, 9, 8, 1, 2, 3, 4],
+        favorite_color: Some("Purple".to_owned()),
+    });
+    book.add_person(Person::from(("alice".to_owned(), 30)));
+    let json = book.to_json();
+    let back = AddressBook::from_json(&json).unwrap();
+    assert_eq!(back.len(), 2);
+    assert_eq!(back["caleb \"the TA\""], book["caleb \"the TA\""]);
+    assert_eq!(back["alice"], book["alice"]);
+    assert_eq!(back.to_json(), json);
+    assert!(AddressBook::from_json("[{\"name\": 3}]").is_err());
+}
+
+// vCard text values escape backslashes, commas, semicolons and newlines
+fn vcf_text(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' | ',' | ';' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+impl AddressBook {
+    // One vCard 3.0 record per person, in name order, so the book can be
+    // imported into a phone's contacts. vCard lines end in \r\n.
+    // DEFAULT_PHONE isn't a real number, so those people get no TEL line.
+    pub fn to_vcf(&self) -> String {
+        let mut out = String::new();
+        for person in self.by_name.values() {
+            let name = vcf_text(&person.name);
+            out.push_str("BEGIN:VCARD\r\nVERSION:3.0\r\n");
+            out.push_str(&format!("N:;{};;;\r\nFN:{}\r\n", name, name));
+            if person.phone != DEFAULT_PHONE {
+                out.push_str(&format!(
+                    "TEL;TYPE=CELL:{}\r\n",
+                    person.display_phone()
+                ));
+            }
+            if let Some(ref color) = person.favorite_color {
+                out.push_str(&format!(
+                    "NOTE:Favorite color: {}\r\n",
+                    vcf_text(color)
+                ));
+            }
+            out.push_str("END:VCARD\r\n");
+        }
+        out
+    }
+}
+
+#[test]
+fn test_to_vcf() {
+    let mut book = AddressBook::new();
+    book.add_person(Person {
+        name: "caleb".to_owned(),
+        age: 26,
+        phone: [2, 1, 5, 8, 9, 8, 1, 2, 3, 4],
+        favorite_color: Some("Purple".to_owned()),
+    });
+    book.add_person(Person::from(("alice".to_owned(), 30)));
+    let vcf = book.to_vcf();
+    assert!(vcf.starts_with("BEGIN:VCARD\r\nVERSION:3.0\r\n"));
+    assert_eq!(vcf.matches("BEGIN:VCARD").count(), 2);
+    assert!(vcf.contains("FN:caleb\r\n"));
+    assert!(vcf.contains("TEL;TYPE=CELL:(215) 898-1234\r\n"));
+    assert!(vcf.contains("NOTE:Favorite color: Purple\r\n"));
+    // alice has DEFAULT_PHONE, so only caleb gets a TEL line
+    assert_eq!(vcf.matches("TEL").count(), 1);
+    assert_eq!(vcf_text("a;b,c"), "a\\;b\\,c");
+}
+
+// Plain data, so the fields can just be public
+#[derive(Debug)]
+pub struct AddressBookStats {
+    pub count: usize,
+    pub avg_age: Option<f64>,
+    pub min_age: Option<u8>,
+    pub max_age: Option<u8>,
+    pub distinct_colors: usize,
+}
+
+impl AddressBook {
+    // Everything in one pass over the people.
+    // The age fields are None for an empty book.
+    pub fn stats(&self) -> AddressBookStats {
+        let mut total_age: u64 = 0;
+        let mut min_age: Option<u8> = None;
+        let mut max_age: Option<u8> = None;
+        let mut colors = HashSet::new();
+        for person in self.by_name.values() {
+            total_age += u64::from(person.age);
+            min_age = Some(min_age.map_or(person.age, |m| m.min(person.age)));
+            max_age = Some(max_age.map_or(person.age, |m| m.max(person.age)));
+            if let Some(ref color) = person.favorite_color {
+                colors.insert(color);
+            }
+        }
+        let count = self.by_name.len();
+        AddressBookStats {
+            count,
+            avg_age: if count > 0 {
+                Some(total_age as f64 / count as f64)
+            } else {
+                None
+            },
+            min_age,
+            max_age,
+            distinct_colors: colors.len(),
+        }
+    }
+}
+
+#[test]
+fn test_stats() {
+    let mut book = AddressBook::new();
+    let empty = book.stats();
+    assert_eq!(empty.count, 0);
+    assert_eq!(empty.avg_age, None);
+
+    for &(name, age, color) in &[
+        ("caleb", 26, "Purple"),
+        ("alice", 30, "Green"),
+        ("bob", 40, "Purple"),
+    ] {
+        book.add_person(Person {
+            name: name.to_owned(),
+            age,
+            phone: DEFAULT_PHONE,
+            favorite_color: Some(color.to_owned()),
+        });
+    }
+    book.add_person(Person::from(("dave".to_owned(), 24)));
+    let stats = book.stats();
+    assert_eq!(stats.count, 4);
+    assert_eq!(stats.avg_age, Some(30.0));
+    assert_eq!(stats.min_age, Some(24));
+    assert_eq!(stats.max_age, Some(40));
+    assert_eq!(stats.distinct_colors, 2);
+}
+
+// Names only; look them up in the books for the details.
+// Each list is sorted.
+#[derive(Debug)]
+pub struct AddressBookDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+impl AddressBook {
+    // What changed going from self to other.
+    // "changed" uses the PartialEq we wrote for Person.
+    pub fn diff(&self, other: &AddressBook) -> AddressBookDiff {
+        let added: Vec<String> = other
+            .by_name
+            .keys()
+            .filter(|name| !self.by_name.contains_key(*name))
+            .cloned()
+            .collect();
+        let mut removed = Vec::new();
+        let mut changed = Vec::new();
+        for (name, person) in &self.by_name {
+            match other.by_name.get(name) {
+                None => removed.push(name.clone()),
+                Some(other_person) if other_person != person => {
+                    changed.push(name.clone())
+                }
+                Some(_) => {}
+            }
+        }
+        AddressBookDiff { added, removed, changed }
+    }
+}
+
+#[test]
+fn test_diff() {
+    let mut before = AddressBook::new();
+    before.add_person(Person::from(("caleb".to_owned(), 26)));
+    before.add_person(Person::from(("alice".to_owned(), 30)));
+    before.add_person(Person::from(("bob".to_owned(), 40)));
+    let mut after = AddressBook::new();
+    after.add_person(Person::from(("caleb".to_owned(), 26)));
+    after.add_person(Person::from(("alice".to_owned(), 31)));
+    after.add_person(Person::from(("dave".to_owned(), 20)));
+
+    let diff = before.diff(&after);
+    assert_eq!(diff.added, vec!["dave"]);
+    assert_eq!(diff.removed, vec!["bob"]);
+    assert_eq!(diff.changed, vec!["alice"]);
+}
+
+// What to do when a name we are adding is already in the book
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CollisionPolicy {
+    // Fail, leaving the book unchanged
+    Reject,
+    // Replace the person already in the book
+    Overwrite,
+    // Keep both, renaming the new one name_2, name_3, ...
+    Suffix,
+}
+
+impl AddressBook {
+    // The name a new person called `name` should be stored under
+    fn resolve_name(
+        &self,
+        name: &str,
+        policy: CollisionPolicy,
+    ) -> Result<String, String> {
+        if !self.by_name.contains_key(name) {
+            return Ok(name.to_string());
+        }
+        match policy {
+            CollisionPolicy::Reject => {
+                Err(format!("{} is already in the address book", name))
+            }
+            CollisionPolicy::Overwrite => Ok(name.to_string()),
+            CollisionPolicy::Suffix => {
+                let mut counter = 2;
+                while self
+                    .by_name
+                    .contains_key(&format!("{}_{}", name, counter))
+                {
+                    counter += 1;
+                }
+                Ok(format!("{}_{}", name, counter))
+            }
+        }
+    }
+
+    // Add everyone from other into self
+    pub fn merge_with(
+        &mut self,
+        other: AddressBook,
+        policy: CollisionPolicy,
+    ) -> Result<(), String> {
+        // Check everything up front, so a rejected merge changes nothing
+        if policy == CollisionPolicy::Reject {
+            for name in other.by_name.keys() {
+                self.resolve_name(name, policy)?;
+            }
+        }
+        for (_, mut person) in other.by_name {
+            let name = self.resolve_name(&person.name, policy)?;
+            if name == person.name {
+                self.add_person(person);
+            } else {
+                // Remember that this is a suffixed copy, for coalesce
+                let original =
+                    std::mem::replace(&mut person.name, name.clone());
+                self.add_person(person);
+                self.suffixed.insert(name, original);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn rename_person(
+        &mut self,
+        old_name: &str,
+        new_name: &str,
+        policy: CollisionPolicy,
+    ) -> Result<(), String> {
+        if !self.by_name.contains_key(old_name) {
+            return Err(format!("No person named {}", old_name));
+        }
+        if old_name == new_name {
+            return Ok(());
+        }
+        let new_name = self.resolve_name(new_name, policy)?;
+        let mut person = self.remove_person(old_name).unwrap();
+        person.name = new_name;
+        self.add_person(person);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+fn colliding_books() -> (AddressBook, AddressBook) {
+    let mut book = AddressBook::new();
+    book.add_person(Person::from(("caleb".to_owned(), 26)));
+    book.add_person(Person::from(("alice".to_owned(), 30)));
+    let mut other = AddressBook::new();
+    other.add_person(Person::from(("caleb".to_owned(), 50)));
+    other.add_person(Person::from(("bob".to_owned(), 40)));
+    (book, other)
+}
+
+#[test]
+fn test_merge_reject() {
+    let (mut book, other) = colliding_books();
+    assert!(book.merge_with(other, CollisionPolicy::Reject).is_err());
+    assert_eq!(book.len(), 2);
+    assert!(!book.contains_name("bob"));
+}
+
+#[test]
+fn test_merge_overwrite() {
+    let (mut book, other) = colliding_books();
+    book.merge_with(other, CollisionPolicy::Overwrite).unwrap();
+    assert_eq!(book.len(), 3);
+    assert_eq!(book["caleb"].age, 50);
+}
+
+#[test]
+fn test_merge_suffix() {
+    let (mut book, other) = colliding_books();
+    book.merge_with(other, CollisionPolicy::Suffix).unwrap();
+    assert_eq!(book.len(), 4);
+    assert_eq!(book["caleb"].age, 26);
+    assert_eq!(book["caleb_2"].age, 50);
+}
+
+#[test]
+fn test_rename_person() {
+    let (mut book, _) = colliding_books();
+    assert!(book
+        .rename_person("caleb", "alice", CollisionPolicy::Reject)
+        .is_err());
+    book.rename_person("caleb", "alice", CollisionPolicy::Suffix).unwrap();
+    assert!(!book.contains_name("caleb"));
+    assert_eq!(book["alice_2"].age, 26);
+}
+
+impl AddressBook {
+    // merge_with, one book at a time, in order. Under Reject the first
+    // collision stops everything, and no partial union is returned.
+    pub fn union_all<I: IntoIterator<Item = AddressBook>>(
+        books: I,
+        policy: CollisionPolicy,
+    ) -> Result<AddressBook, String> {
+        let mut union = AddressBook::new();
+        for book in books {
+            union.merge_with(book, policy)?;
+        }
+        Ok(union)
+    }
+}
+
+#[test]
+fn test_union_all() {
+    let (book, other) = colliding_books();
+    let mut third = AddressBook::new();
+    third.add_person(Person::from(("dave".to_owned(), 60)));
+    let union = AddressBook::union_all(
+        vec![book, other, third],
+        CollisionPolicy::Suffix,
+    )
+    .unwrap();
+    // caleb is in the first two books
+    assert_eq!(union.len(), 5);
+    assert_eq!(union["caleb"].age, 26);
+    assert_eq!(union["caleb_2"].age, 50);
+    assert!(union.contains_name("dave"));
+    assert!(union.is_consistent());
+
+    let (book, other) = colliding_books();
+    assert!(AddressBook::union_all(vec![book, other], CollisionPolicy::Reject)
+        .is_err());
+}
+
+impl Person {
+    // How much we actually know about someone: one point for a real
+    // phone number (not DEFAULT_PHONE), one for a known favorite color.
+    // Name and age are always present, so they don't count.
+    pub fn completeness(&self) -> u8 {
+        (self.phone != DEFAULT_PHONE) as u8
+            + self.favorite_color.is_some() as u8
+    }
+}
+
+impl Person {
+    // Could these two records describe the same person? The age has to
+    // match, and the phone and color have to agree wherever both are
+    // known. (The name isn't compared: that's the part that differs.)
+    pub fn could_be_same(&self, other: &Person) -> bool {
+        let phones_agree = self.phone == other.phone
+            || self.phone == DEFAULT_PHONE
+            || other.phone == DEFAULT_PHONE;
+        let colors_agree = match (&self.favorite_color, &other.favorite_color) {
+            (Some(a), Some(b)) => a == b,
+            _ => true,
+        };
+        self.age == other.age && phones_agree && colors_agree
+    }
+}
+
+impl AddressBook {
+    // Undo duplicate imports. merge_with(.., Suffix) stores someone who
+    // collided with "caleb" as "caleb_2" and remembers that it did. For
+    // each such copy, if "caleb" is still here and could_be_same as the
+    // copy, fold the copy into "caleb", filling in the phone or color
+    // if only the copy knew it. The result is at least as complete as
+    // either record. A person who just happens to be named "caleb_2",
+    // or a copy that disagrees with the original, is left alone.
+    // Only merge_with records copies: a book rebuilt any other way
+    // (collect, update_all, ...) has none, and coalesce does nothing.
+    pub fn coalesce(&mut self) {
+        let copies: Vec<(String, String)> = self
+            .suffixed
+            .iter()
+            .map(|(copy, original)| (copy.clone(), original.clone()))
+            .collect();
+        for (copy_name, original) in copies {
+            // If the original name is itself a copy (caleb_2_2 collided
+            // with the copy caleb_2), whoever is there now isn't the
+            // person this one collided with.
+            if self.suffixed.contains_key(&original) {
+                continue;
+            }
+            let same = match (
+                self.by_name.get(&copy_name),
+                self.by_name.get(&original),
+            ) {
+                (Some(copy), Some(kept)) => copy.could_be_same(kept),
+                _ => false,
+            };
+            if !same {
+                continue;
+            }
+            let copy = self.remove_person(&copy_name).unwrap();
+            let mut kept = self.remove_person(&original).unwrap();
+            if kept.phone == DEFAULT_PHONE {
+                kept.phone = copy.phone;
+            }
+            if kept.favorite_color.is_none() {
+                kept.favorite_color = copy.favorite_color;
+            }
+            self.add_person(kept);
+        }
+    }
+}
+
+#[test]
+fn test_coalesce() {
+    let mut book = AddressBook::new();
+    book.add_person(Person::from(("caleb".to_owned(), 26)));
+    book.add_person(Person::from(("alice".to_owned(), 30)));
+    let mut other = AddressBook::new();
+    other.add_person(Person {
+        name: "caleb".to_owned(),
+        age: 26,
+        phone: [2, 1, 5, 5, 5, 5, 1, 2, 3, 4],
+        favorite_color: Some("Purple".to_owned()),
+    });
+    book.merge_with(other, CollisionPolicy::Suffix).unwrap();
+    assert!(book.contains_name("caleb_2"));
+
+    book.coalesce();
+    assert_eq!(book.len(), 2);
+    assert!(!book.contains_name("caleb_2"));
+    assert_eq!(book["caleb"].favorite_color(), Some("Purple"));
+    assert_eq!(book["caleb"].completeness(), 2);
+    assert!(book.find_by_phone(&[2, 1, 5, 5, 5, 5, 1, 2, 3, 4]).is_some());
+    assert!(book.is_consistent());
+}
+
+#[test]
+fn test_coalesce_chained_suffix() {
+    let mut book = AddressBook::new();
+    book.add_person(Person::from(("caleb".to_owned(), 26)));
+    let mut other = AddressBook::new();
+    other.add_person(Person::from(("caleb".to_owned(), 26)));
+    other.add_person(Person {
+        name: "caleb_2".to_owned(),
+        age: 26,
+        phone: [2, 1, 5, 5, 5, 5, 1, 2, 3, 4],
+        favorite_color: None,
+    });
+    book.merge_with(other, CollisionPolicy::Suffix).unwrap();
+    assert!(book.contains_name("caleb_2") && book.contains_name("caleb_2_2"));
+
+    // caleb_2 is the imported caleb and folds back in. caleb_2_2 is the
+    // imported book's own caleb_2, a different person, so it stays.
+    book.coalesce();
+    assert_eq!(book.len(), 2);
+    assert_eq!(book["caleb"].completeness(), 0);
+    assert_eq!(book["caleb_2_2"].phone, [2, 1, 5, 5, 5, 5, 1, 2, 3, 4]);
+    assert!(book.is_consistent());
+}
+
+#[test]
+fn test_coalesce_keeps_distinct_people() {
+    let mut book = AddressBook::new();
+    book.add_person(Person::from(("room".to_owned(), 26)));
+    // Really named room_2, not a copy of room
+    book.add_person(Person {
+        name: "room_2".to_owned(),
+        age: 40,
+        phone: [2, 1, 5, 5, 5, 5, 1, 2, 3, 4],
+        favorite_color: None,
+    });
+    // A suffixed copy that disagrees with room (different age)
+    let mut other = AddressBook::new();
+    other.add_person(Person::from(("room".to_owned(), 50)));
+    book.merge_with(other, CollisionPolicy::Suffix).unwrap();
+    assert!(book.contains_name("room_3"));
+
+    book.coalesce();
+    assert_eq!(book.len(), 3);
+    assert_eq!(book["room"].age, 26);
+    assert_eq!(book["room_2"].age, 40);
+    assert_eq!(book["room_3"].age, 50);
+    assert!(book.is_consistent());
+}
+
+impl AddressBook {
+    // People whose names are in both books, with the details from self
+    pub fn intersect(&self, other: &AddressBook) -> AddressBook {
+        self.by_name
+            .values()
+            .filter(|person| other.contains_name(&person.name))
+            .cloned()
+            .collect()
+    }
+}
+
+#[test]
+fn test_intersect() {
+    let (book, other) = colliding_books();
+    let shared = book.intersect(&other);
+    assert_eq!(shared.len(), 1);
+    // caleb is in both; the age comes from book, not other
+    assert_eq!(shared["caleb"].age, 26);
+    assert!(!shared.contains_name("alice"));
+    assert!(!shared.contains_name("bob"));
+}
+
+impl AddressBook {
+    // Split the book in two: (everyone pred accepts, everyone else).
+    // Taking self by value lets us move people instead of cloning them,
+    // and going through add_person builds each book's indexes from scratch.
+    pub fn partition(
+        self,
+        pred: impl Fn(&Person) -> bool,
+    ) -> (AddressBook, AddressBook) {
+        let mut matching = AddressBook::new();
+        let mut rest = AddressBook::new();
+        for person in self.by_name.into_values() {
+            if pred(&person) {
+                matching.add_person(person);
+            } else {
+                rest.add_person(person);
+            }
+        }
+        (matching, rest)
+    }
+}
+
+#[test]
+fn test_partition_book() {
+    let mut book = AddressBook::new();
+    for &(name, age) in
+        &[("erin", 12), ("caleb", 26), ("alice", 17), ("bob", 40)]
+    {
+        book.add_person(Person::from((name.to_owned(), age)));
+    }
+    let total = book.len();
+    let (minors, adults) = book.partition(|p| p.age < 18);
+    assert_eq!(minors.len() + adults.len(), total);
+    assert_eq!(minors.len(), 2);
+    assert!(minors.contains_name("alice") && minors.contains_name("erin"));
+    assert!(adults.contains_name("bob") && adults.contains_name("caleb"));
+    assert!(minors.is_consistent() && adults.is_consistent());
+}
+
+/*
+    A couple more traits before we move on to iplementing our own.
+
+    - Read / Write
+
+      For objects that can be read or written to.
+
+    println!("{}", ...)
+    write!(f, "{}", ...)
+    ^ for f implementing the Write trait.
+
+    Q: Why is this useful?
+
+    A: Very different objects are writable.
+    - a terminal interface
+    - standard output
+    - a network connection
+    - a internal buffer
+    - A file
+
+    Recall: Traits define shared behavior across different types!
+
+    Similarly Read is for stdin-like buffers, input files, etc.
+*/
+
+/*
+    Iterator
+*/
+
+pub fn iterator_example() {
+    for x in 0..10 {
+        println!("{}", x);
+    }
+    // Internally: for x in I means that I is a type implementing
+    // the Iterator trait.
+
+    // Most collections offer a way of getting an iterator from
+    // the object; where "iterator" means something that implements
+    // the Iterator trait.
+
+    let v = vec![1, 2, 3];
+    for x in v.iter() {
+        println!("{}", x);
+    }
+
+    let h: HashMap<usize, usize> = HashMap::new();
+    for x in h.iter() {
         println!("{:?}", x);
     }
 }
 
+// The other direction: FromIterator is what .collect() uses to build
+// a collection out of an iterator. Implement it, and our own type
+// works with collect() too.
+impl FromIterator<Person> for AddressBook {
+    fn from_iter<I: IntoIterator<Item = Person>>(iter: I) -> Self {
+        let mut book = AddressBook::new();
+        book.bulk_add(iter);
+        book
+    }
+}
+
+#[test]
+fn test_collect_address_book() {
+    let people = vec![
+        Person::from(("caleb".to_owned(), 26)),
+        Person::from(("alice".to_owned(), 30)),
+    ];
+    let book: AddressBook = people.into_iter().collect();
+    assert_eq!(book.len(), 2);
+    assert!(book.contains_name("alice"));
+}
+
 /*
     Not technically in the standard library, but so widespread and
     idiomatic that they are standard:
@@ -528,7 +2448,7 @@ impl AddressBook {
     Having identified the common behavior, let's write a trait.
 */
 
-trait Summary {
+pub trait Summary {
     // Documentation: this trait encapsulates the behavior of printing
     // a short summary of a datatype.
     // (i.e. abbreviated)
@@ -610,6 +2530,192 @@ impl Summary for AddressBook {
     }
 }
 
+// Generic impls: if T can be summarized, so can Option<T>
+impl<T: Summary> Summary for Option<T> {
+    fn short_summary(&self) -> String {
+        match self {
+            Some(x) => x.short_summary(),
+            None => "(none)".to_string(),
+        }
+    }
+    fn long_summary(&self) -> String {
+        match self {
+            Some(x) => x.long_summary(),
+            None => "(none)".to_string(),
+        }
+    }
+    fn summary_in_lines(&self, lines: usize) -> String {
+        match self {
+            Some(x) => x.summary_in_lines(lines),
+            None if lines > 0 => "(none)".to_string(),
+            None => "".to_string(),
+        }
+    }
+}
+
+#[test]
+fn test_summary_option() {
+    let phone = Some(PhoneNumber([2, 1, 5, 8, 9, 8, 1, 2, 3, 4]));
+    assert_eq!(phone.short_summary(), "[2, 1, 5, 8, 9, 8, 1, 2, 3, 4]");
+    let missing: Option<PhoneNumber> = None;
+    assert_eq!(missing.short_summary(), "(none)");
+    assert_eq!(missing.long_summary(), "(none)");
+}
+
+// Same idea for tuples: summarize each part, joined with " | "
+impl<A: Summary, B: Summary> Summary for (A, B) {
+    fn short_summary(&self) -> String {
+        format!("{} | {}", self.0.short_summary(), self.1.short_summary())
+    }
+    fn long_summary(&self) -> String {
+        format!("{} | {}", self.0.long_summary(), self.1.long_summary())
+    }
+    fn summary_in_lines(&self, lines: usize) -> String {
+        format!(
+            "{} | {}",
+            self.0.summary_in_lines(lines),
+            self.1.summary_in_lines(lines),
+        )
+    }
+}
+
+impl<A: Summary, B: Summary, C: Summary> Summary for (A, B, C) {
+    fn short_summary(&self) -> String {
+        format!(
+            "{} | {} | {}",
+            self.0.short_summary(),
+            self.1.short_summary(),
+            self.2.short_summary(),
+        )
+    }
+    fn long_summary(&self) -> String {
+        format!(
+            "{} | {} | {}",
+            self.0.long_summary(),
+            self.1.long_summary(),
+            self.2.long_summary(),
+        )
+    }
+    fn summary_in_lines(&self, lines: usize) -> String {
+        format!(
+            "{} | {} | {}",
+            self.0.summary_in_lines(lines),
+            self.1.summary_in_lines(lines),
+            self.2.summary_in_lines(lines),
+        )
+    }
+}
+
+#[test]
+fn test_summary_tuple() {
+    let pair = (
+        PhoneNumber([2, 1, 5, 8, 9, 8, 1, 2, 3, 4]),
+        PhoneNumber([5, 5, 5, 5, 5, 5, 5, 5, 5, 5]),
+    );
+    assert_eq!(
+        pair.short_summary(),
+        "[2, 1, 5, 8, 9, 8, 1, 2, 3, 4] | [5, 5, 5, 5, 5, 5, 5, 5, 5, 5]"
+    );
+    let triple = (pair.0, pair.1, None::<PhoneNumber>);
+    assert!(triple.long_summary().ends_with(" | (none)"));
+}
+
+// A Vec can only hold one type. To mix two Summary types in one Vec,
+// wrap each element in an enum that is one or the other.
+pub enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+impl<L: Summary, R: Summary> Summary for Either<L, R> {
+    fn short_summary(&self) -> String {
+        match self {
+            Either::Left(l) => l.short_summary(),
+            Either::Right(r) => r.short_summary(),
+        }
+    }
+    fn long_summary(&self) -> String {
+        match self {
+            Either::Left(l) => l.long_summary(),
+            Either::Right(r) => r.long_summary(),
+        }
+    }
+    fn summary_in_lines(&self, lines: usize) -> String {
+        match self {
+            Either::Left(l) => l.summary_in_lines(lines),
+            Either::Right(r) => r.summary_in_lines(lines),
+        }
+    }
+}
+
+#[test]
+fn test_summary_either() {
+    let mixed: Vec<Either<PhoneNumber, Person2>> = vec![
+        Either::Left(PhoneNumber([2, 1, 5, 8, 9, 8, 1, 2, 3, 4])),
+        Either::Right(Person2 {
+            name: "caleb".to_owned(),
+            age: 26,
+            phone: PhoneNumber([5, 5, 5, 5, 5, 5, 5, 5, 5, 5]),
+            favorite_color: "Purple".to_owned(),
+        }),
+    ];
+    let summaries: Vec<String> =
+        mixed.iter().map(|x| x.short_summary()).collect();
+    assert_eq!(
+        summaries,
+        vec!["[2, 1, 5, 8, 9, 8, 1, 2, 3, 4]", "Person: caleb, age 26"]
+    );
+}
+
+// Generic over any T: Summary. This is why Summary has to be pub:
+// a pub function can't have a private trait in its bounds.
+pub fn summarize_all<T: Summary>(
+    items: &[T],
+    lines_each: usize,
+) -> Vec<String> {
+    items.iter().map(|item| item.summary_in_lines(lines_each)).collect()
+}
+
+#[test]
+fn test_summarize_all() {
+    let phones = [
+        PhoneNumber([2, 1, 5, 8, 9, 8, 1, 2, 3, 4]),
+        PhoneNumber([5, 5, 5, 5, 5, 5, 5, 5, 5, 5]),
+    ];
+    assert_eq!(
+        summarize_all(&phones, 1),
+        vec![
+            "[2, 1, 5, 8, 9, 8, 1, 2, 3, 4]",
+            "[5, 5, 5, 5, 5, 5, 5, 5, 5, 5]"
+        ]
+    );
+    assert_eq!(summarize_all(&phones, 0), vec!["", ""]);
+}
+
+// A blanket impl: every type that implements Summary gets OneLine for
+// free, without writing a single impl per type.
+pub trait OneLine {
+    fn one_line(&self) -> String;
+}
+
+impl<T: Summary> OneLine for T {
+    fn one_line(&self) -> String {
+        self.short_summary()
+    }
+}
+
+#[test]
+fn test_one_line() {
+    let caleb = Person2 {
+        name: "caleb".to_owned(),
+        age: 26,
+        phone: PhoneNumber([2, 1, 5, 8, 9, 8, 1, 2, 3, 4]),
+        favorite_color: "Purple".to_owned(),
+    };
+    assert_eq!(caleb.one_line(), "Person: caleb, age 26");
+    assert_eq!(caleb.one_line(), caleb.short_summary());
+}
+
 /*
     One last thing about implementing your own traits:
     Sometimes you want do define derived functionality from